@@ -1,3 +1,4 @@
+use crate::file_directive::{DIRECTIVE_LINES, FileDirectives};
 use crate::languages::{LanguageHandler, get_handler};
 use crate::rules::preservation::PreservationRule;
 use tree_sitter::Node;
@@ -11,8 +12,41 @@ pub struct CommentInfo {
     pub node_type: &'static str,
     pub should_preserve: bool,
     pub is_documentation: bool,
+    /// Why the comment is kept, when `should_preserve` is set by the visitor.
+    pub preserved_by: Option<Preservation>,
+    /// Index of the removal rule that overrode the preservation rule which
+    /// would otherwise have kept the comment.
+    pub removed_by: Option<usize>,
 }
 
+/// What kept a comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preservation {
+    /// The language handler's `should_preserve_comment` insisted on it, as Go's
+    /// does for `//go:` directives.
+    Handler,
+    /// The preservation rule at this index of the visitor's rules matched.
+    Rule(usize),
+    /// Another line of its contiguous comment block carries `~keep`; see
+    /// [`CommentVisitor::extend_keep_blocks`].
+    KeepBlock,
+    /// It lies within, or marks, an `uncomment:off` ... `uncomment:on` region;
+    /// see [`CommentVisitor::apply_directives`].
+    Region,
+    /// It is, or directly follows, an `uncomment:keep-next` comment.
+    KeepNext,
+    /// It holds a file-level directive such as `uncomment: ignore-file`, which
+    /// must survive for later runs to honour it.
+    FileDirective,
+}
+
+/// Starts a region whose comments are all kept.
+pub const REGION_OFF: &str = "uncomment:off";
+/// Ends a region started by [`REGION_OFF`].
+pub const REGION_ON: &str = "uncomment:on";
+/// Keeps the next comment, wherever it is.
+pub const KEEP_NEXT: &str = "uncomment:keep-next";
+
 impl CommentInfo {
     #[must_use]
     pub fn new(node: Node) -> Self {
@@ -24,6 +58,8 @@ impl CommentInfo {
             node_type: node.kind(),
             should_preserve: false,
             is_documentation: false,
+            preserved_by: None,
+            removed_by: None,
         }
     }
 
@@ -39,6 +75,14 @@ impl CommentInfo {
         self
     }
 
+    /// Keep the comment for `reason`, or leave it removable when `None`.
+    #[must_use]
+    pub const fn with_preserved_by(mut self, reason: Option<Preservation>) -> Self {
+        self.should_preserve = reason.is_some();
+        self.preserved_by = reason;
+        self
+    }
+
     /// Extract comment content from source by byte range.
     #[inline]
     pub fn content<'a>(&self, source: &'a str) -> &'a str {
@@ -49,6 +93,7 @@ impl CommentInfo {
 pub struct CommentVisitor<'a> {
     source: &'a str,
     preservation_rules: &'a [PreservationRule],
+    removal_rules: &'a [PreservationRule],
     comments: Vec<CommentInfo>,
     comment_node_types: &'a [String],
     doc_comment_node_types: &'a [String],
@@ -68,6 +113,7 @@ impl<'a> CommentVisitor<'a> {
         Self {
             source,
             preservation_rules,
+            removal_rules: &[],
             comments: Vec::with_capacity(32),
             comment_node_types,
             doc_comment_node_types,
@@ -75,6 +121,14 @@ impl<'a> CommentVisitor<'a> {
         }
     }
 
+    /// Rules that remove a comment a preservation rule would keep, unless it
+    /// carries `~keep`. Comments the language handler keeps are not affected.
+    #[must_use]
+    pub const fn with_removal_rules(mut self, removal_rules: &'a [PreservationRule]) -> Self {
+        self.removal_rules = removal_rules;
+        self
+    }
+
     pub fn visit_node(&mut self, node: Node) {
         self.visit_node_recursive(node, None);
     }
@@ -96,9 +150,21 @@ impl<'a> CommentVisitor<'a> {
                 .unwrap_or(false);
 
             let content = comment_info.content(self.source);
-            let should_preserve = forced_preserve || self.should_preserve_comment(&comment_info, content);
-            let comment_with_preservation = comment_info.with_preservation(should_preserve);
-            self.comments.push(comment_with_preservation);
+            let mut preserved_by = if forced_preserve {
+                Some(Preservation::Handler)
+            } else {
+                self.matching_rule(&comment_info, content).map(Preservation::Rule)
+            };
+            if matches!(preserved_by, Some(Preservation::Rule(_))) && !content.contains("~keep") {
+                comment_info.removed_by = self
+                    .removal_rules
+                    .iter()
+                    .position(|rule| rule.matches(&comment_info, content));
+                if comment_info.removed_by.is_some() {
+                    preserved_by = None;
+                }
+            }
+            self.comments.push(comment_info.with_preserved_by(preserved_by));
         }
 
         let mut cursor = node.walk();
@@ -107,6 +173,18 @@ impl<'a> CommentVisitor<'a> {
         }
     }
 
+    /// Every comment found, kept or not, in visiting order.
+    #[must_use]
+    pub fn comments(&self) -> &[CommentInfo] {
+        &self.comments
+    }
+
+    /// Name of the language-specific handler in use, or `None` for the default one.
+    #[must_use]
+    pub fn handler_name(&self) -> Option<&'static str> {
+        self.language_handler.name()
+    }
+
     #[must_use]
     pub fn get_comments_to_remove(&self) -> Vec<&CommentInfo> {
         self.comments
@@ -115,6 +193,24 @@ impl<'a> CommentVisitor<'a> {
             .collect()
     }
 
+    /// How the visitor sees `node`: `None` when it is not a comment, otherwise
+    /// whether it counts as a doc comment.
+    #[must_use]
+    pub fn classify_node(&self, node: &Node, parent: Option<Node>) -> Option<bool> {
+        if !self.is_comment_node(node, parent) {
+            return None;
+        }
+        let is_doc = self
+            .language_handler
+            .is_documentation_comment(node, parent, self.source)
+            .unwrap_or_else(|| {
+                self.doc_comment_node_types
+                    .iter()
+                    .any(|node_type| node_type == node.kind())
+            });
+        Some(is_doc)
+    }
+
     fn is_comment_node(&self, node: &Node, parent: Option<Node>) -> bool {
         let kind = node.kind();
 
@@ -135,13 +231,11 @@ impl<'a> CommentVisitor<'a> {
         false
     }
 
-    fn should_preserve_comment(&self, comment: &CommentInfo, content: &str) -> bool {
-        for rule in self.preservation_rules {
-            if rule.matches(comment, content) {
-                return true;
-            }
-        }
-        false
+    /// Index of the first preservation rule that keeps `comment`.
+    fn matching_rule(&self, comment: &CommentInfo, content: &str) -> Option<usize> {
+        self.preservation_rules
+            .iter()
+            .position(|rule| rule.matches(comment, content))
     }
 
     /// Extend `~keep` preservation across contiguous single-line comment blocks.
@@ -166,34 +260,97 @@ impl<'a> CommentVisitor<'a> {
     /// The pass is purely additive: it only ever sets `should_preserve = true`,
     /// never clears it, so running it after the per-comment decisions is safe.
     pub fn extend_keep_blocks(&mut self) {
+        for run in self.line_comment_runs() {
+            let has_keep = run
+                .iter()
+                .any(|&i| self.comments[i].content(self.source).contains("~keep"));
+            if has_keep {
+                for &i in &run {
+                    let comment = &mut self.comments[i];
+                    if !comment.should_preserve {
+                        comment.should_preserve = true;
+                        comment.preserved_by = Some(Preservation::KeepBlock);
+                        comment.removed_by = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the `uncomment:off` / `uncomment:on` and `uncomment:keep-next`
+    /// directives, which work in any comment syntax since they are matched on
+    /// comment text.
+    ///
+    /// Every comment from an `uncomment:off` to the next `uncomment:on` (or the
+    /// end of the file), markers included, is kept. A comment holding both
+    /// markers leaves the region open or closed by whichever comes last. An
+    /// `uncomment:keep-next` comment keeps itself and the next comment in source
+    /// order, however far below it is. Comments holding a
+    /// [file-level directive](crate::file_directive) are kept as well.
+    ///
+    /// Like [`extend_keep_blocks`](Self::extend_keep_blocks) the pass only ever
+    /// keeps comments, and it overrides removal rules.
+    pub fn apply_directives(&mut self) {
+        let mut indices: Vec<usize> = (0..self.comments.len()).collect();
+        indices.sort_by_key(|&i| self.comments[i].start_byte);
+
+        let mut in_region = false;
+        let mut keep_next = false;
+        for i in indices {
+            let content = self.comments[i].content(self.source);
+            let off = rfind_marker(content, REGION_OFF);
+            let on = rfind_marker(content, REGION_ON);
+            let reason = if in_region || off.is_some() || on.is_some() {
+                Some(Preservation::Region)
+            } else if self.comments[i].start_row < DIRECTIVE_LINES
+                && FileDirectives::parse(content) != FileDirectives::default()
+            {
+                Some(Preservation::FileDirective)
+            } else if keep_next || rfind_marker(content, KEEP_NEXT).is_some() {
+                Some(Preservation::KeepNext)
+            } else {
+                None
+            };
+            in_region = match (off, on) {
+                (Some(off), Some(on)) => off > on,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => in_region,
+            };
+            keep_next = rfind_marker(content, KEEP_NEXT).is_some();
+
+            let comment = &mut self.comments[i];
+            if reason.is_some() && !comment.should_preserve {
+                comment.should_preserve = true;
+                comment.preserved_by = reason;
+                comment.removed_by = None;
+            }
+        }
+    }
+
+    /// Runs of standalone single-line comments on consecutive rows, as indices
+    /// into [`comments`](Self::comments) in source order. A lone comment is a run
+    /// of one; trailing and multi-line comments belong to no run.
+    #[must_use]
+    pub fn line_comment_runs(&self) -> Vec<Vec<usize>> {
         // Standalone single-line comments, in source order.
         let mut indices: Vec<usize> = (0..self.comments.len())
             .filter(|&i| self.is_standalone_single_line(&self.comments[i]))
             .collect();
         indices.sort_by_key(|&i| self.comments[i].start_byte);
 
-        let mut run_start = 0;
-        while run_start < indices.len() {
-            // Extend the run while the next comment sits on the immediately
-            // following row (consecutive standalone single-line comments).
-            let mut run_end = run_start;
-            while run_end + 1 < indices.len()
-                && self.comments[indices[run_end + 1]].start_row == self.comments[indices[run_end]].start_row + 1
-            {
-                run_end += 1;
-            }
-
-            let has_keep = indices[run_start..=run_end]
-                .iter()
-                .any(|&i| self.comments[i].content(self.source).contains("~keep"));
-            if has_keep {
-                for &i in &indices[run_start..=run_end] {
-                    self.comments[i].should_preserve = true;
+        let mut runs: Vec<Vec<usize>> = Vec::new();
+        for i in indices {
+            match runs.last_mut() {
+                // Extend the run while the next comment sits on the immediately
+                // following row.
+                Some(run) if self.comments[i].start_row == self.comments[run[run.len() - 1]].start_row + 1 => {
+                    run.push(i);
                 }
+                _ => runs.push(vec![i]),
             }
-
-            run_start = run_end + 1;
         }
+        runs
     }
 
     /// Whether `comment` is a single-line comment node that occupies its line
@@ -210,6 +367,16 @@ impl<'a> CommentVisitor<'a> {
     }
 }
 
+/// Byte offset of the last `marker` in `content` that ends the comment or is
+/// followed by whitespace, `*/` or `-->`, so `uncomment:offset` and
+/// `uncomment:only-commented-code` are not read as region markers.
+fn rfind_marker(content: &str, marker: &str) -> Option<usize> {
+    content.rmatch_indices(marker).map(|(at, _)| at).find(|&at| {
+        let rest = &content[at + marker.len()..];
+        rest.is_empty() || rest.starts_with(char::is_whitespace) || rest.starts_with("*/") || rest.starts_with("-->")
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,9 +391,23 @@ mod tests {
             node_type,
             should_preserve: false,
             is_documentation: false,
+            preserved_by: None,
+            removed_by: None,
         }
     }
 
+    #[test]
+    fn test_markers_end_at_a_token_boundary() {
+        assert_eq!(rfind_marker("// uncomment:off", REGION_OFF), Some(3));
+        assert_eq!(rfind_marker("/* uncomment:off*/", REGION_OFF), Some(3));
+        assert_eq!(rfind_marker("<!-- uncomment:on -->", REGION_ON), Some(5));
+        assert_eq!(rfind_marker("# uncomment:keep-next\tnote", KEEP_NEXT), Some(2));
+        assert_eq!(rfind_marker("# uncomment:offset notes", REGION_OFF), None);
+        assert_eq!(rfind_marker("// uncomment:only-commented-code=true", REGION_ON), None);
+        assert_eq!(rfind_marker("# uncomment:keep-nextline", KEEP_NEXT), None);
+        assert_eq!(rfind_marker("# uncomment:off uncomment:onward", REGION_ON), None);
+    }
+
     #[test]
     fn test_comment_info_creation() {
         let comment = create_mock_comment("line_comment");
@@ -0,0 +1,216 @@
+//! Crash-safe file replacement that keeps the original file's metadata.
+//!
+//! New content goes to a temporary file in the target's directory (so the final
+//! rename never crosses filesystems), inherits the original's permissions and,
+//! on Unix, its owner, and is then renamed over the target. An interrupted run
+//! therefore leaves either the old or the new file, never a truncated one.
+
+use anyhow::{Context, Result, bail};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How [`write_atomic`] treats links and timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Write through symlinks to their target. When false, symlinks are refused.
+    pub follow_symlinks: bool,
+    /// Restore the original modification time after writing.
+    pub preserve_mtime: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: true,
+            preserve_mtime: false,
+        }
+    }
+}
+
+/// Whether `path` itself (not its target) is a symlink.
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Replace the contents of the existing file at `path`.
+///
+/// Symlinks are resolved first, so the link survives and its target is
+/// rewritten. Files with several hard links are rewritten in place instead,
+/// because renaming over one name would silently detach it from the others.
+pub fn write_atomic(path: &Path, contents: &[u8], options: WriteOptions) -> Result<()> {
+    let target = if is_symlink(path) {
+        if !options.follow_symlinks {
+            bail!("Refusing to write through symlink: {}", path.display());
+        }
+        fs::canonicalize(path).with_context(|| format!("Failed to resolve symlink: {}", path.display()))?
+    } else {
+        path.to_path_buf()
+    };
+
+    let metadata = fs::metadata(&target).with_context(|| format!("Failed to read metadata: {}", target.display()))?;
+    let mtime = options.preserve_mtime.then(|| metadata.modified()).transpose()?;
+
+    if link_count(&metadata) > 1 {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&target)
+            .with_context(|| format!("Failed to open file: {}", target.display()))?;
+        file.write_all(contents)
+            .with_context(|| format!("Failed to write file: {}", target.display()))?;
+        if let Some(mtime) = mtime {
+            file.set_modified(mtime)
+                .with_context(|| format!("Failed to restore mtime: {}", target.display()))?;
+        }
+        return Ok(());
+    }
+
+    let temp_path = temp_path_for(&target);
+    let result = (|| -> Result<()> {
+        let mut temp = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .with_context(|| format!("Failed to create temporary file: {}", temp_path.display()))?;
+        temp.write_all(contents)
+            .with_context(|| format!("Failed to write temporary file: {}", temp_path.display()))?;
+        temp.set_permissions(metadata.permissions())
+            .with_context(|| format!("Failed to copy permissions to {}", temp_path.display()))?;
+        copy_ownership(&temp, &metadata);
+        if let Some(mtime) = mtime {
+            temp.set_modified(mtime)
+                .with_context(|| format!("Failed to restore mtime: {}", target.display()))?;
+        }
+        temp.sync_all()
+            .with_context(|| format!("Failed to flush temporary file: {}", temp_path.display()))?;
+        fs::rename(&temp_path, &target).with_context(|| format!("Failed to replace {}", target.display()))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// A hidden sibling of `target`, unique within this process.
+fn temp_path_for(target: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+    target.with_file_name(format!(".{file_name}.uncomment-{}-{unique}.tmp", std::process::id()))
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::nlink(metadata)
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &fs::Metadata) -> u64 {
+    1
+}
+
+/// Best-effort: only root (or the owner, for the group) may chown, and a file
+/// we could rewrite but not re-own is still better written than not.
+#[cfg(unix)]
+fn copy_ownership(file: &File, metadata: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    let _ = std::os::unix::fs::fchown(file, Some(metadata.uid()), Some(metadata.gid()));
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_file: &File, _metadata: &fs::Metadata) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    #[test]
+    fn replaces_content_without_leaving_temp_files() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "// old\n").unwrap();
+
+        write_atomic(&file, b"new\n", WriteOptions::default()).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_mode_bits() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("run.sh");
+        fs::write(&file, "#!/bin/sh\n# x\n").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o750)).unwrap();
+
+        write_atomic(&file, b"#!/bin/sh\n", WriteOptions::default()).unwrap();
+
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o750);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_or_refuses_symlinks() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("real.rs");
+        let link = dir.path().join("link.rs");
+        fs::write(&target, "// old\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let refuse = WriteOptions {
+            follow_symlinks: false,
+            ..WriteOptions::default()
+        };
+        assert!(write_atomic(&link, b"new\n", refuse).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "// old\n");
+
+        write_atomic(&link, b"new\n", WriteOptions::default()).unwrap();
+        assert!(is_symlink(&link));
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rewrites_hard_links_in_place() {
+        let dir = TempDir::new().unwrap();
+        let first = dir.path().join("a.rs");
+        let second = dir.path().join("b.rs");
+        fs::write(&first, "// old\n").unwrap();
+        fs::hard_link(&first, &second).unwrap();
+
+        write_atomic(&first, b"new\n", WriteOptions::default()).unwrap();
+
+        assert_eq!(fs::read_to_string(&second).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn preserves_mtime_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "// old\n").unwrap();
+        let past = SystemTime::now() - Duration::from_secs(86_400);
+        File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        let options = WriteOptions {
+            preserve_mtime: true,
+            ..WriteOptions::default()
+        };
+        write_atomic(&file, b"new\n", options).unwrap();
+
+        assert_eq!(fs::metadata(&file).unwrap().modified().unwrap(), past);
+    }
+}
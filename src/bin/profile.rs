@@ -212,10 +212,13 @@ fn run_uncomment(files: &[PathBuf]) -> anyhow::Result<Duration> {
         remove_doc: false,
         custom_preserve_patterns: vec![],
         use_default_ignores: true,
+        only_commented_code: false,
         dry_run: true,
         show_diff: false,
         respect_gitignore: false,
         traverse_git_repos: false,
+        line_filter: None,
+        language: None,
     };
 
     for file in files {
@@ -234,10 +237,13 @@ fn run_uncomment_with_stats(files: &[PathBuf]) -> anyhow::Result<ProcessResult>
         remove_doc: false,
         custom_preserve_patterns: vec![],
         use_default_ignores: true,
+        only_commented_code: false,
         dry_run: true,
         show_diff: false,
         respect_gitignore: false,
         traverse_git_repos: false,
+        line_filter: None,
+        language: None,
     };
 
     let mut modified_files = 0;
@@ -1,17 +1,35 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// Usage examples and preservation notes shown under `--help`.
 const AFTER_LONG_HELP: &str = "Examples:
   uncomment src/                     Remove comments from every file under src/
   uncomment src/ --dry-run --diff    Preview changes as a diff, write nothing
+  uncomment src/ --check             Fail (exit 3) if any comment would be removed
+  uncomment - --stdin-filepath a.rs  Filter stdin to stdout, treating it as a.rs
+  uncomment src/ --patch out.patch   Write changes as a patch for `git apply`
+  uncomment . --list-comments csv    Inventory every comment for an audit
   uncomment main.rs --remove-doc     Also strip doc comments and docstrings
+  uncomment . --only-commented-code  Strip commented-out code, keep prose
+  uncomment . --include-generated    Also rewrite generated files (skipped by default)
   uncomment . -j 0                   Process the whole tree using all CPU cores
+  uncomment bin/deploy --language py Parse an extensionless script as Python
+  uncomment --staged                 Strip comments from staged changes (pre-commit)
+  uncomment src/ --since main        Only touch lines changed since the main branch
+  uncomment a.rs --lines 10:40       Only strip comments within lines 10-40
   uncomment init                     Generate a .uncommentrc.toml for this project
+  uncomment undo                     Restore the files changed by the last run
+  uncomment languages                List languages and their comment node kinds
+  uncomment explain src/a.rs:12      Show why the comment on line 12 is kept or removed
+  uncomment inspect src/a.rs         Print the syntax tree to find comment node kinds
+  uncomment stats src/               Count comments per language and directory
+  uncomment config show src/a.rs     Show the settings for a file and where each is set
 
 Preserved by default: TODO, FIXME, HACK, XXX, NOSONAR, the ~keep marker, doc
 comments, and linting directives (eslint-disable, clippy::, noqa, ...). Override
-with the flags above or a .uncommentrc.toml (see `uncomment init`).";
+with the flags above or a .uncommentrc.toml (see `uncomment init`). Comments
+between uncomment:off and uncomment:on, and the one after uncomment:keep-next,
+are always kept.";
 
 #[derive(Parser, Debug)]
 #[command(
@@ -54,14 +72,131 @@ pub enum Commands {
         #[arg(short, long, help = "Interactive mode to select languages and options")]
         interactive: bool,
     },
+
+    /// ~keep Restore the files rewritten by a previous run
+    #[command(about = "Restore the files rewritten by a previous run from its journal")]
+    Undo {
+        /// ~keep Run to undo; defaults to the most recent one
+        #[arg(value_name = "RUN_ID")]
+        run_id: Option<String>,
+
+        /// ~keep List journaled runs instead of restoring
+        #[arg(long, help = "List journaled runs instead of restoring")]
+        list: bool,
+    },
+
+    /// ~keep List supported languages and their tree-sitter node kinds
+    #[command(about = "List supported languages, their comment node kinds and grammar status")]
+    Languages {
+        /// ~keep Print machine-readable JSON
+        #[arg(long, help = "Print the list as JSON")]
+        json: bool,
+    },
+
+    /// ~keep Explain why each comment in a file is kept or removed
+    #[command(about = "Explain why each comment in a file is kept or removed")]
+    Explain {
+        /// ~keep File to explain, optionally narrowed to the comments on one line
+        #[arg(value_name = "FILE[:LINE]")]
+        target: String,
+    },
+
+    /// ~keep Print a file's syntax tree with its comment nodes highlighted
+    #[command(about = "Print a file's syntax tree, highlighting the nodes treated as comments")]
+    Inspect {
+        /// ~keep File to parse
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// ~keep Include anonymous nodes (punctuation and keywords)
+        #[arg(long, help = "Include anonymous nodes such as punctuation and keywords")]
+        all: bool,
+    },
+
+    /// ~keep Report comment counts and density per language and directory
+    #[command(about = "Report comment counts, density and what a run would keep, per language and directory")]
+    Stats {
+        /// ~keep Files or directories to measure (supports glob patterns)
+        #[arg(
+            value_name = "PATH",
+            required = true,
+            help = "Files, directories, or glob patterns to measure"
+        )]
+        paths: Vec<String>,
+
+        /// ~keep Print machine-readable JSON
+        #[arg(long, help = "Print the statistics as JSON")]
+        json: bool,
+
+        /// ~keep Measure generated files too
+        #[arg(
+            long = "include-generated",
+            help = "Measure generated files instead of skipping them"
+        )]
+        include_generated: bool,
+    },
+
+    /// ~keep Inspect configuration
+    #[command(about = "Inspect the configuration that applies to a file")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+/// ~keep Subcommands of `uncomment config`
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// ~keep Show the effective configuration for a file
+    #[command(about = "Show the effective settings for a file, where each was set, and its preservation rules")]
+    Show {
+        /// ~keep File to resolve the configuration for; it need not exist
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+    },
+}
+
+/// ~keep Format of the run report written to stdout
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// ~keep Human-readable, colored output
+    #[default]
+    Text,
+    /// ~keep A single JSON document with every file and the summary
+    Json,
+    /// ~keep One JSON object per line: a record per file, then the summary
+    Ndjson,
+    /// ~keep SARIF 2.1.0 log for code-scanning tools
+    Sarif,
+}
+
+/// ~keep Format of the comment inventory written by `--list-comments`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentListFormat {
+    /// ~keep A JSON array with one object per comment
+    Json,
+    /// ~keep CSV with a header row and one row per comment
+    Csv,
 }
 
 #[derive(Parser, Debug)]
 pub struct ProcessArgs {
     /// ~keep Files or directories to process (supports glob patterns)
-    #[arg(value_name = "PATH", help = "Files, directories, or glob patterns to process")]
+    #[arg(
+        value_name = "PATH",
+        help = "Files, directories, or glob patterns to process (`-` reads stdin)"
+    )]
     pub paths: Vec<String>,
 
+    /// ~keep Virtual file name used for language detection and config when reading stdin
+    #[arg(
+        long = "stdin-filepath",
+        value_name = "NAME",
+        help = "File name to treat stdin (`-`) as, for language detection and config lookup",
+        help_heading = "File selection"
+    )]
+    pub stdin_filepath: Option<PathBuf>,
+
     /// ~keep Remove TODO comments (normally preserved)
     #[arg(
         short = 'r',
@@ -107,6 +242,14 @@ pub struct ProcessArgs {
     )]
     pub no_default_ignores: bool,
 
+    /// ~keep Remove only commented-out code, keeping prose comments
+    #[arg(
+        long = "only-commented-code",
+        help = "Remove only comments that parse as code in the file's language, keeping prose",
+        help_heading = "Comment selection"
+    )]
+    pub only_commented_code: bool,
+
     /// ~keep Show what would be changed without modifying files
     #[arg(
         short = 'n',
@@ -116,6 +259,14 @@ pub struct ProcessArgs {
     )]
     pub dry_run: bool,
 
+    /// ~keep Fail with a non-zero exit status if any file would be modified
+    #[arg(
+        long,
+        help = "Exit with status 3 if any file would be modified; never writes (for CI)",
+        help_heading = "Output"
+    )]
+    pub check: bool,
+
     /// ~keep Show line-by-line diffs of removed comments
     #[arg(
         long = "diff",
@@ -124,6 +275,61 @@ pub struct ProcessArgs {
     )]
     pub diff: bool,
 
+    /// ~keep Do not journal originals for `uncomment undo`
+    #[arg(
+        long = "no-journal",
+        help = "Do not save originals under .uncomment/ for `uncomment undo`",
+        help_heading = "Output"
+    )]
+    pub no_journal: bool,
+
+    /// ~keep Refuse to write through symlinks
+    #[arg(
+        long = "no-follow-symlinks",
+        help = "Skip files that are symlinks instead of rewriting their targets",
+        help_heading = "Output"
+    )]
+    pub no_follow_symlinks: bool,
+
+    /// ~keep Keep each rewritten file's original modification time
+    #[arg(
+        long = "preserve-mtime",
+        help = "Keep the original modification time of rewritten files",
+        help_heading = "Output"
+    )]
+    pub preserve_mtime: bool,
+
+    /// ~keep Write a unified patch instead of modifying files
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the changes as a unified patch to FILE (`-` for stdout) instead of modifying files",
+        help_heading = "Output"
+    )]
+    pub patch: Option<PathBuf>,
+
+    /// ~keep Format of the run report
+    #[arg(
+        long = "output-format",
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = OutputFormat::Text,
+        help = "Report format: human-readable text, json/ndjson for tooling, or sarif for code scanning",
+        help_heading = "Output"
+    )]
+    pub output_format: OutputFormat,
+
+    /// ~keep List every comment instead of removing any
+    #[arg(
+        long = "list-comments",
+        value_enum,
+        value_name = "FORMAT",
+        help = "Write every comment, with whether it would be kept, to stdout as json or csv; modifies nothing",
+        help_heading = "Output",
+        conflicts_with_all = ["patch", "check", "diff", "output_format", "stdin_filepath"]
+    )]
+    pub list_comments: Option<CommentListFormat>,
+
     /// ~keep Show detailed processing information
     #[arg(
         short = 'v',
@@ -159,6 +365,53 @@ pub struct ProcessArgs {
     )]
     pub traverse_git_repos: bool,
 
+    /// ~keep Process generated files instead of skipping them
+    #[arg(
+        long = "include-generated",
+        help = "Process generated files (codegen headers, suffixes, linguist-generated) instead of skipping them",
+        help_heading = "File selection"
+    )]
+    pub include_generated: bool,
+
+    /// ~keep Process the files staged in git instead of walking PATHs
+    #[arg(
+        long,
+        help = "Process staged content from the git index (PATHs filter it) and write results back to the index and working tree",
+        help_heading = "File selection",
+        conflicts_with = "stdin_filepath"
+    )]
+    pub staged: bool,
+
+    /// ~keep Parse named files as this language instead of detecting it
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Parse files named on the command line, and stdin, as language NAME (or extension, e.g. `py`); files found by walking a directory keep their detected language",
+        help_heading = "File selection"
+    )]
+    pub language: Option<String>,
+
+    /// ~keep Only remove comments on lines changed since a git revision
+    #[arg(
+        long,
+        value_name = "REV",
+        help = "Only remove comments on lines added or changed since REV (untracked files count as changed)",
+        help_heading = "File selection",
+        conflicts_with = "stdin_filepath"
+    )]
+    pub since: Option<String>,
+
+    /// ~keep Only remove comments that lie entirely within these lines
+    #[arg(
+        long,
+        value_name = "START:END",
+        value_parser = crate::line_filter::parse_line_range,
+        help = "Only remove comments lying entirely within lines START:END of a single file (can be used multiple times)",
+        help_heading = "File selection",
+        conflicts_with_all = ["since", "staged"]
+    )]
+    pub lines: Vec<std::ops::RangeInclusive<usize>>,
+
     /// ~keep Number of parallel threads (0 = number of CPU cores)
     #[arg(
         short = 'j',
@@ -182,6 +435,13 @@ pub struct ProcessArgs {
 }
 
 impl ProcessArgs {
+    pub fn write_options(&self) -> crate::atomic_write::WriteOptions {
+        crate::atomic_write::WriteOptions {
+            follow_symlinks: !self.no_follow_symlinks,
+            preserve_mtime: self.preserve_mtime,
+        }
+    }
+
     pub fn processing_options(&self) -> crate::processor::ProcessingOptions {
         crate::processor::ProcessingOptions {
             remove_todo: self.remove_todo,
@@ -189,10 +449,18 @@ impl ProcessArgs {
             remove_doc: self.remove_doc,
             custom_preserve_patterns: self.ignore_patterns.clone(),
             use_default_ignores: !self.no_default_ignores,
-            dry_run: self.dry_run,
+            only_commented_code: self.only_commented_code,
+            dry_run: self.dry_run || self.check || self.patch.is_some() || self.list_comments.is_some(),
             show_diff: self.diff,
             respect_gitignore: !self.no_gitignore,
             traverse_git_repos: self.traverse_git_repos,
+            line_filter: (!self.lines.is_empty()).then(|| {
+                crate::line_filter::LineFilter::new(
+                    self.lines.iter().cloned(),
+                    crate::line_filter::LineFilterMode::Contain,
+                )
+            }),
+            language: self.language.clone(),
         }
     }
 }
@@ -279,4 +547,503 @@ impl Cli {
 
         Ok(())
     }
+
+    /// ~keep Handle the languages command
+    pub fn handle_languages_command(json: bool) -> anyhow::Result<()> {
+        use crate::ui;
+        use anyhow::Context;
+
+        let current_dir = std::env::current_dir()?;
+        let config_manager =
+            crate::config::ConfigManager::new(&current_dir).context("Failed to initialize configuration manager")?;
+        let mut registry = crate::languages::LanguageRegistry::new();
+        registry.register_configured_languages(&config_manager.get_all_languages());
+        let summaries = registry.summaries();
+
+        if json {
+            let mut stdout = std::io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &summaries).context("Failed to serialize languages")?;
+            use std::io::Write;
+            writeln!(stdout)?;
+            return Ok(());
+        }
+
+        let list = |items: &[String]| {
+            if items.is_empty() {
+                ui::dim("-")
+            } else {
+                items.join(", ")
+            }
+        };
+        for summary in &summaries {
+            let grammar = if summary.grammar_available {
+                ui::success("available")
+            } else {
+                ui::warn("downloaded on first use")
+            };
+            anstream::println!(
+                "{}  {}",
+                ui::bold(&summary.name),
+                ui::dim(format!(
+                    "{} · handler: {}",
+                    summary.source,
+                    summary.handler.unwrap_or("default")
+                ))
+            );
+            let extensions: Vec<String> = summary
+                .extensions
+                .iter()
+                .map(|ext| format!(".{}", ext.trim_start_matches('.')))
+                .collect();
+            anstream::println!("  {}  {}", ui::dim("extensions:   "), list(&extensions));
+            anstream::println!("  {}  {}", ui::dim("comment nodes:"), list(&summary.comment_types));
+            anstream::println!("  {}  {}", ui::dim("doc nodes:    "), list(&summary.doc_comment_types));
+            anstream::println!("  {}  {} ({grammar})", ui::dim("grammar:      "), summary.tslp_name);
+        }
+        anstream::println!("{} {} languages", ui::dim(ui::BULLET), ui::accent(summaries.len()));
+        Ok(())
+    }
+
+    /// ~keep Handle the explain command
+    pub fn handle_explain_command(target: &str, config: Option<&std::path::Path>) -> anyhow::Result<()> {
+        use crate::ui;
+        use anyhow::Context;
+
+        let (path, line) = crate::explain::parse_target(target).map_err(anyhow::Error::msg)?;
+        let current_dir = std::env::current_dir()?;
+        let config_manager = crate::config::ConfigManager::load(&current_dir, config)?;
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+        let mut processor = crate::processor::Processor::new_with_config(&config_manager);
+        let explanation = processor.explain(&path, content, &config_manager, None)?;
+        let decisions = explanation.decisions(line);
+
+        anstream::println!(
+            "{}  {}",
+            ui::path(&path),
+            ui::dim(format!(
+                "{} · handler: {}",
+                explanation.language,
+                explanation.handler.unwrap_or("default")
+            ))
+        );
+        for decision in &decisions {
+            let comment = decision.comment;
+            let verdict = if decision.kept {
+                ui::success("kept   ")
+            } else {
+                ui::danger("removed")
+            };
+            let kind = if comment.is_documentation {
+                format!("doc, {}", decision.kind.as_str())
+            } else {
+                decision.kind.as_str().to_string()
+            };
+            let text = comment.content(&explanation.content);
+            anstream::println!(
+                "  {:<8} {verdict}  {} {}",
+                ui::line_span(decision.lines.0 - 1, decision.lines.1 - 1),
+                text.lines().next().unwrap_or_default().trim(),
+                ui::dim(format!("({kind})"))
+            );
+
+            let mut why = decision.reason.clone();
+            if let Some(setting) = &decision.setting {
+                let sources: Vec<String> = setting.sources.iter().map(ToString::to_string).collect();
+                why.push_str(&format!(" · {} ({})", setting.assignment, sources.join(", ")));
+            }
+            anstream::println!("  {:<8} {}", "", ui::dim(why));
+        }
+
+        let kept = decisions.iter().filter(|decision| decision.kept).count();
+        match line {
+            Some(line) if decisions.is_empty() => {
+                anstream::println!("{} No comment on line {line}.", ui::dim(ui::BULLET));
+            }
+            _ => anstream::println!(
+                "{} {} comments: {} kept, {} removed",
+                ui::dim(ui::BULLET),
+                ui::accent(decisions.len()),
+                kept,
+                decisions.len() - kept
+            ),
+        }
+        Ok(())
+    }
+
+    /// ~keep Handle the inspect command
+    pub fn handle_inspect_command(
+        file: &std::path::Path,
+        all: bool,
+        config: Option<&std::path::Path>,
+    ) -> anyhow::Result<()> {
+        use crate::inspect::NodeComment;
+        use crate::ui;
+        use anyhow::Context;
+
+        let current_dir = std::env::current_dir()?;
+        let config_manager = crate::config::ConfigManager::load(&current_dir, config)?;
+        let content =
+            std::fs::read_to_string(file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+
+        let mut processor = crate::processor::Processor::new_with_config(&config_manager);
+        let inspection = processor.inspect(file, &content, &config_manager)?;
+
+        let list = |items: &[String]| {
+            if items.is_empty() {
+                "-".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        anstream::println!(
+            "{}  {}",
+            ui::path(file),
+            ui::dim(format!("{} · grammar: {}", inspection.language, inspection.tslp_name))
+        );
+        anstream::println!(
+            "{}",
+            ui::dim(format!(
+                "comment nodes: {} · doc nodes: {}",
+                list(&inspection.comment_types),
+                list(&inspection.doc_comment_types)
+            ))
+        );
+
+        for node in inspection.visible_nodes(all) {
+            let kind = if node.named {
+                node.kind.to_string()
+            } else {
+                format!("{:?}", node.kind)
+            };
+            let span = format!(
+                "[{}..{}] {}:{}-{}:{}",
+                node.start_byte,
+                node.end_byte,
+                node.start.0 + 1,
+                node.start.1 + 1,
+                node.end.0 + 1,
+                node.end.1 + 1
+            );
+            let marker = match node.comment {
+                Some(NodeComment::Comment) => format!("  {}", ui::success("◆ comment")),
+                Some(NodeComment::Doc) => format!("  {}", ui::accent("◆ doc comment")),
+                None => String::new(),
+            };
+            let indent = "  ".repeat(node.depth);
+            if node.comment.is_some() {
+                anstream::println!("{indent}{}  {}{marker}", ui::bold(kind), ui::dim(span));
+            } else {
+                anstream::println!("{indent}{kind}  {}", ui::dim(span));
+            }
+        }
+
+        let comments = inspection.nodes.iter().filter(|node| node.comment.is_some()).count();
+        anstream::println!(
+            "{} {} nodes, {} treated as comments",
+            ui::dim(ui::BULLET),
+            ui::accent(inspection.nodes.len()),
+            ui::accent(comments)
+        );
+        Ok(())
+    }
+
+    /// ~keep Handle the stats command
+    pub fn handle_stats_command(report: &crate::stats::StatsReport, json: bool) -> anyhow::Result<()> {
+        use crate::stats::CommentStats;
+        use crate::ui;
+
+        if json {
+            return report.write_json(&mut std::io::stdout().lock());
+        }
+
+        let ratio = |stats: &CommentStats| match stats.comment_ratio() {
+            Some(ratio) => format!("{ratio:.2}"),
+            None => "-".to_string(),
+        };
+        let table = |heading: &str, rows: Vec<(String, &CommentStats)>| {
+            let width = rows
+                .iter()
+                .map(|(name, _)| name.chars().count())
+                .chain([heading.len()])
+                .max()
+                .unwrap_or_default();
+            anstream::println!(
+                "{}",
+                ui::dim(format!(
+                    "{heading:<width$}  {:>5}  {:>8}  {:>13}  {:>10}  {:>5}  {:>4}  {:>4}  {:>7}",
+                    "Files", "Comments", "Comment lines", "Code lines", "Ratio", "Docs", "Kept", "Removed"
+                ))
+            );
+            for (name, stats) in rows {
+                anstream::println!(
+                    "{}  {:>5}  {:>8}  {:>13}  {:>10}  {:>5}  {:>4}  {:>4}  {:>7}",
+                    ui::bold(format!("{name:<width$}")),
+                    stats.files,
+                    stats.comments,
+                    stats.comment_lines,
+                    stats.code_lines,
+                    ratio(stats),
+                    stats.documentation,
+                    stats.preserved,
+                    stats.removed
+                );
+                if !stats.preserved_by.is_empty() {
+                    let kept_by: Vec<String> = stats
+                        .preserved_by
+                        .iter()
+                        .map(|(kept_by, count)| format!("{} {count}", kept_by.as_str()))
+                        .collect();
+                    anstream::println!(
+                        "{:<width$}  {}",
+                        "",
+                        ui::dim(format!("kept by: {}", kept_by.join(" · ")))
+                    );
+                }
+            }
+        };
+
+        table(
+            "Language",
+            report
+                .languages
+                .iter()
+                .map(|(language, stats)| (language.clone(), stats))
+                .collect(),
+        );
+        anstream::println!();
+        table(
+            "Directory",
+            report
+                .directories
+                .iter()
+                .map(|(directory, stats)| (directory.display().to_string(), stats))
+                .collect(),
+        );
+
+        let total = &report.total;
+        anstream::println!();
+        anstream::println!(
+            "{} {} files, {} comments on {} lines: {} kept, {} removed · comment-to-code ratio {}",
+            ui::dim(ui::BULLET),
+            ui::accent(total.files),
+            ui::accent(total.comments),
+            total.comment_lines,
+            total.preserved,
+            total.removed,
+            ratio(total)
+        );
+        Ok(())
+    }
+
+    /// ~keep Handle the config show command
+    pub fn handle_config_show_command(path: &std::path::Path, config: Option<&std::path::Path>) -> anyhow::Result<()> {
+        use crate::config::SettingSource;
+        use crate::explain::rule_setting;
+        use crate::ui;
+
+        if path.is_dir() {
+            anyhow::bail!(
+                "{} is a directory; name a file, since its language decides the language-level settings",
+                path.display()
+            );
+        }
+        let current_dir = std::env::current_dir()?;
+        let config_manager = crate::config::ConfigManager::load(&current_dir, config)?;
+        // The content only matters for shebang and modeline detection, so a file
+        // that does not exist yet is resolved by its name alone.
+        let content = std::fs::read_to_string(path).unwrap_or_default();
+
+        let processor = crate::processor::Processor::new_with_config(&config_manager);
+        let (language_config, config) = processor.resolve_config(path, &content, &config_manager, None)?;
+        let language = language_config.name.to_lowercase();
+
+        let detection = if config.language.is_some() {
+            "forced by [patterns]"
+        } else {
+            "detected"
+        };
+        anstream::println!("{}  {}", ui::path(path), ui::dim(format!("{language} ({detection})")));
+
+        anstream::println!("\n{}", ui::bold("Config files (lowest to highest precedence)"));
+        let files = config_manager.config_files_for(path);
+        if files.is_empty() {
+            anstream::println!("  {}", ui::dim("none; built-in defaults apply"));
+        }
+        for file in files {
+            anstream::println!("  {}", ui::path(file));
+        }
+
+        anstream::println!("\n{}", ui::bold("Settings"));
+        let sources = &config.sources;
+        let setting = |key: &str, value: String, source: &SettingSource| {
+            anstream::println!("  {key:<20} {value:<8} {}", ui::dim(source));
+        };
+        setting("remove_todos", config.remove_todos.to_string(), &sources.remove_todos);
+        setting("remove_fixme", config.remove_fixme.to_string(), &sources.remove_fixme);
+        setting("remove_docs", config.remove_docs.to_string(), &sources.remove_docs);
+        setting(
+            "use_default_ignores",
+            config.use_default_ignores.to_string(),
+            &sources.use_default_ignores,
+        );
+        setting(
+            "respect_gitignore",
+            config.respect_gitignore.to_string(),
+            &sources.respect_gitignore,
+        );
+        setting(
+            "only_commented_code",
+            config.only_commented_code.to_string(),
+            &sources.only_commented_code,
+        );
+        setting(
+            "traverse_git_repos",
+            config.traverse_git_repos.to_string(),
+            &sources.traverse_git_repos,
+        );
+        if config.ignore_file {
+            setting("ignore_file", "true".to_string(), &sources.ignore_file);
+        }
+        if config.preserve_patterns.is_empty() {
+            anstream::println!("  {:<20} {}", "preserve_patterns", ui::dim("none"));
+        }
+        for pattern in &config.preserve_patterns {
+            let from: Vec<String> = sources
+                .preserve_pattern_sources(pattern)
+                .map(ToString::to_string)
+                .collect();
+            anstream::println!(
+                "  {:<20} {:<8} {}",
+                "preserve_patterns",
+                format!("{pattern:?}"),
+                ui::dim(from.join(", "))
+            );
+        }
+        for pattern in &config.remove_patterns {
+            let from: Vec<String> = sources
+                .remove_pattern_sources(pattern)
+                .map(ToString::to_string)
+                .collect();
+            anstream::println!(
+                "  {:<20} {:<8} {}",
+                "remove_patterns",
+                format!("{pattern:?}"),
+                ui::dim(from.join(", "))
+            );
+        }
+        for (key, patterns, removal) in [
+            ("preserve_regex", &config.preserve_regex, false),
+            ("remove_regex", &config.remove_regex, true),
+        ] {
+            for pattern in patterns {
+                let from: Vec<String> = sources
+                    .regex_sources(pattern, removal)
+                    .map(ToString::to_string)
+                    .collect();
+                anstream::println!("  {:<20} {:<8} {}", key, pattern.to_string(), ui::dim(from.join(", ")));
+            }
+        }
+        let language_source = match config_manager.language_config_file(&language) {
+            Some(file) if config.language_config.is_some() => format!("[languages.{language}] in {}", file.display()),
+            _ => "built-in".to_string(),
+        };
+        anstream::println!("  {:<20} {:<8} {}", "language", language, ui::dim(language_source));
+
+        let rules = processor.preservation_rule_set(&config);
+        anstream::println!(
+            "\n{} {}",
+            ui::bold("Preservation rules"),
+            ui::dim(format!("({}, checked in order)", rules.len()))
+        );
+        let mut previous: Option<String> = None;
+        for (rule, origin) in &rules {
+            let heading = match rule_setting(rule, *origin, &config) {
+                Some(setting) => {
+                    let from: Vec<String> = setting.sources.iter().map(ToString::to_string).collect();
+                    format!("{} ({})", setting.assignment, from.join(", "))
+                }
+                None => "always on".to_string(),
+            };
+            // Name each origin once, above the run of rules it added.
+            if previous.as_ref() != Some(&heading) {
+                anstream::println!("  {}", ui::dim(&heading));
+                previous = Some(heading);
+            }
+            anstream::println!("    {rule}");
+        }
+
+        let removal_rules = processor.removal_rules(&config);
+        if !removal_rules.is_empty() {
+            anstream::println!(
+                "\n{} {}",
+                ui::bold("Removal rules"),
+                ui::dim("(override the preservation rules unless a comment has ~keep)")
+            );
+            for rule in &removal_rules {
+                anstream::println!("    {rule}");
+            }
+        }
+        Ok(())
+    }
+
+    /// ~keep Handle the undo command
+    pub fn handle_undo_command(run_id: Option<&str>, list: bool) -> anyhow::Result<()> {
+        use crate::journal::{self, UndoStatus};
+        use crate::ui;
+
+        let current_dir = std::env::current_dir()?;
+
+        if list {
+            let runs = journal::list_runs(&current_dir)?;
+            if runs.is_empty() {
+                anstream::println!("{} No journaled runs in this directory.", ui::dim(ui::BULLET));
+            }
+            for run in runs {
+                anstream::println!(
+                    "{}  {}",
+                    ui::accent(&run.run_id),
+                    ui::dim(format!("{} file(s)", run.files))
+                );
+            }
+            return Ok(());
+        }
+
+        let report = journal::undo(&current_dir, run_id)?;
+        for (path, status) in &report.files {
+            match status {
+                UndoStatus::Restored => {
+                    anstream::println!("{} {}", ui::success("Restored:"), ui::path(path));
+                }
+                UndoStatus::AlreadyRestored => {
+                    anstream::println!("{} {}", ui::dim("Already original:"), ui::path(path));
+                }
+                UndoStatus::ChangedSinceRun => anstream::eprintln!(
+                    "{} {} changed since the run; left untouched",
+                    ui::warn("skipped:"),
+                    ui::path(path)
+                ),
+                UndoStatus::Missing => {
+                    anstream::eprintln!("{} {} no longer exists", ui::warn("skipped:"), ui::path(path))
+                }
+            }
+        }
+
+        if !report.is_complete() {
+            return Err(anyhow::anyhow!(
+                "Run {} was only partially undone; its journal was kept in {}",
+                report.run_id,
+                journal::history_dir(&current_dir).join(&report.run_id).display()
+            ));
+        }
+
+        anstream::println!(
+            "{} {} {}",
+            ui::success(ui::CHECK),
+            ui::success("Undid run"),
+            ui::accent(&report.run_id)
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,236 @@
+//! Telling commented-out code from prose (`--only-commented-code`).
+//!
+//! A comment's body, with its delimiters stripped, is re-parsed with the file's
+//! own grammar. It counts as code when the parse has no errors and the body is
+//! token-dense: prose that happens to parse, like Python's `Note: slow` (an
+//! annotated assignment), has about one token per word, while code has
+//! punctuation and operators between its words.
+
+use crate::ast::visitor::CommentInfo;
+use tree_sitter::{Node, Parser};
+
+/// Syntax tokens needed per word for a body to count as code; see [`is_dense`].
+const MIN_TOKENS_PER_WORD: f64 = 1.5;
+
+/// Line comment markers, longest first so `///` is not read as `//` plus `/`.
+pub(crate) const LINE_MARKERS: &[&str] = &["///", "//!", "//", "##", "#", "--", ";;", ";", "%"];
+
+/// Block comment delimiters, longest opener first.
+pub(crate) const BLOCK_DELIMITERS: &[(&str, &str)] = &[
+    ("/**", "*/"),
+    ("/*!", "*/"),
+    ("/*", "*/"),
+    ("<!--", "-->"),
+    ("--[[", "]]"),
+    ("{-", "-}"),
+    ("(*", "*)"),
+];
+
+/// The rest of `line` after the comment marker it starts with (a block opener,
+/// a line marker or a `*` gutter), or `None` when it does not start like a
+/// comment.
+pub(crate) fn comment_line_body(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    BLOCK_DELIMITERS
+        .iter()
+        .map(|(open, _)| *open)
+        .chain(LINE_MARKERS.iter().copied())
+        .chain(["*"])
+        .find_map(|marker| line.strip_prefix(marker))
+}
+
+/// What a comment's body is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// Parses as code in the file's language.
+    Code,
+    Prose,
+}
+
+impl CommentKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommentKind::Code => "commented-out code",
+            CommentKind::Prose => "prose",
+        }
+    }
+}
+
+/// Classify every comment of a file whose grammar `parser` is set to.
+///
+/// `runs` are the runs of standalone single-line comments from
+/// [`CommentVisitor::line_comment_runs`](crate::ast::visitor::CommentVisitor::line_comment_runs):
+/// code commented out line by line is only valid as a whole, so a run that
+/// parses as code makes all of its lines code. Otherwise each comment is judged
+/// on its own. Documentation comments are always prose.
+pub fn classify_comments(
+    parser: &mut Parser,
+    source: &str,
+    comments: &[CommentInfo],
+    runs: &[Vec<usize>],
+) -> Vec<CommentKind> {
+    let judgements: Vec<Judgement> = comments
+        .iter()
+        .map(|comment| {
+            if comment.is_documentation {
+                Judgement::Invalid
+            } else {
+                judge(parser, &comment_body(comment.content(source)))
+            }
+        })
+        .collect();
+    let mut kinds: Vec<CommentKind> = judgements
+        .iter()
+        .map(|judgement| match judgement {
+            Judgement::Dense => CommentKind::Code,
+            _ => CommentKind::Prose,
+        })
+        .collect();
+
+    for run in runs.iter().filter(|run| run.len() > 1) {
+        if run.iter().any(|&index| comments[index].is_documentation) {
+            continue;
+        }
+        // A heading such as `# Note: slow` parses on its own in some grammars;
+        // leave it out rather than let the code below carry it.
+        let sparse = |index: &&usize| judgements[**index] == Judgement::Sparse;
+        let start = run.iter().take_while(sparse).count();
+        let end = run.len() - run[start..].iter().rev().take_while(sparse).count();
+        let lines = &run[start..end];
+        if lines.len() < 2 {
+            continue;
+        }
+
+        let body: Vec<String> = lines
+            .iter()
+            .map(|&index| comment_body(comments[index].content(source)))
+            .collect();
+        if judge(parser, &body.join("\n")) == Judgement::Dense {
+            for &index in lines {
+                kinds[index] = CommentKind::Code;
+            }
+        }
+    }
+
+    kinds
+}
+
+/// How a comment body fares as code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Judgement {
+    /// Does not parse, or is empty.
+    Invalid,
+    /// Parses, but with too few tokens per word to be more than prose.
+    Sparse,
+    /// Parses and is [`is_dense`].
+    Dense,
+}
+
+/// The text of a comment without its delimiters: one line marker per line, or
+/// the block opener, closer and any `*` gutter.
+pub fn comment_body(text: &str) -> String {
+    let trimmed = text.trim();
+    if let Some((open, close)) = BLOCK_DELIMITERS.iter().find(|(open, _)| trimmed.starts_with(open)) {
+        let inner = &trimmed[open.len()..];
+        let inner = inner.strip_suffix(close).unwrap_or(inner);
+        let gutter = *open == "/*" || *open == "/**" || *open == "/*!";
+        return inner
+            .lines()
+            .map(|line| {
+                let line = line.trim_start();
+                match line.strip_prefix('*') {
+                    Some(rest) if gutter => rest,
+                    _ => line,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    trimmed
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            LINE_MARKERS
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+                .unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse `body` with the grammar `parser` is set to and judge the result.
+fn judge(parser: &mut Parser, body: &str) -> Judgement {
+    if body.trim().is_empty() {
+        return Judgement::Invalid;
+    }
+    match parser.parse(body, None) {
+        Some(tree) if !tree.root_node().has_error() => {
+            if is_dense(tree.root_node(), body) {
+                Judgement::Dense
+            } else {
+                Judgement::Sparse
+            }
+        }
+        _ => Judgement::Invalid,
+    }
+}
+
+/// Whether the tree has more than [`MIN_TOKENS_PER_WORD`] tokens per word of
+/// `body`, a word being an identifier-like run starting with a letter.
+fn is_dense(root: Node, body: &str) -> bool {
+    let words = body
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .count();
+    let tokens = leaf_count(root);
+    words > 0 && tokens as f64 > words as f64 * MIN_TOKENS_PER_WORD
+}
+
+fn leaf_count(node: Node) -> usize {
+    if node.child_count() == 0 {
+        return usize::from(node.end_byte() > node.start_byte());
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).map(leaf_count).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser_for(language: &str) -> Parser {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_language_pack::get_language(language).unwrap())
+            .unwrap();
+        parser
+    }
+
+    #[test]
+    fn strips_line_and_block_delimiters() {
+        assert_eq!(comment_body("// let x = 1;"), " let x = 1;");
+        assert_eq!(comment_body("/// Docs"), " Docs");
+        assert_eq!(comment_body("# x = 1"), " x = 1");
+        assert_eq!(comment_body("/* a();\n * b();\n */"), "a();\n b();\n");
+        assert_eq!(comment_body("<!-- <p>hi</p> -->"), "<p>hi</p> ");
+    }
+
+    #[test]
+    fn tells_code_from_prose() {
+        let mut python = parser_for("python");
+        assert_eq!(judge(&mut python, "total = compute(a, b)"), Judgement::Dense);
+        assert_eq!(judge(&mut python, "count += 1"), Judgement::Dense);
+        assert_eq!(judge(&mut python, "Note: slow"), Judgement::Sparse);
+        assert_eq!(
+            judge(&mut python, "Compute the total before returning."),
+            Judgement::Invalid
+        );
+
+        let mut rust = parser_for("rust");
+        assert_eq!(judge(&mut rust, "let x = compute(a, b);"), Judgement::Dense);
+        assert_eq!(judge(&mut rust, "Returns the sum of a and b"), Judgement::Invalid);
+        assert_eq!(judge(&mut rust, "fn f() {"), Judgement::Invalid);
+    }
+}
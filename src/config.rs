@@ -1,7 +1,11 @@
+use crate::line_filter::LineFilter;
+use crate::rules::preservation::RegexPattern;
 use ahash::{AHashMap, AHashSet};
 use anyhow::{Context, Result};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
@@ -37,8 +41,15 @@ pub struct Config {
     #[serde(default)]
     pub languages: HashMap<String, LanguageConfig>,
 
+    /// Kept in declaration order: when several globs match a file, the entry
+    /// declared last wins.
     #[serde(default)]
-    pub patterns: HashMap<String, PatternConfig>,
+    pub patterns: IndexMap<String, PatternConfig>,
+
+    /// The `[global]` keys the file writes itself; its other flags only hold
+    /// defaults, so it is not their source.
+    #[serde(skip)]
+    pub global_keys: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,9 +68,27 @@ pub struct GlobalConfig {
     #[serde(default)]
     pub preserve_patterns: Vec<String>,
 
+    /// Patterns whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_patterns: Vec<String>,
+
+    /// Regexes whose matching comments are preserved
+    #[serde(default)]
+    pub preserve_regex: Vec<RegexPattern>,
+
+    /// Regexes whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_regex: Vec<RegexPattern>,
+
     #[serde(default = "default_true")]
     pub use_default_ignores: bool,
 
+    /// Remove only comments that are commented-out code, keeping prose
+    #[serde(default = "default_false")]
+    pub only_commented_code: bool,
+
     #[serde(default = "default_true")]
     pub respect_gitignore: bool,
 
@@ -81,6 +110,20 @@ pub struct LanguageConfig {
     #[serde(default)]
     pub preserve_patterns: Vec<String>,
 
+    /// Patterns whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_patterns: Vec<String>,
+
+    /// Regexes whose matching comments are preserved
+    #[serde(default)]
+    pub preserve_regex: Vec<RegexPattern>,
+
+    /// Regexes whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_regex: Vec<RegexPattern>,
+
     /// Override global remove_todos setting
     pub remove_todos: Option<bool>,
 
@@ -90,6 +133,8 @@ pub struct LanguageConfig {
     pub remove_docs: Option<bool>,
 
     pub use_default_ignores: Option<bool>,
+
+    pub only_commented_code: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,7 +150,37 @@ pub struct PatternConfig {
     #[serde(default)]
     pub preserve_patterns: Vec<String>,
 
+    /// Patterns whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_patterns: Vec<String>,
+
+    /// Regexes whose matching comments are preserved
+    #[serde(default)]
+    pub preserve_regex: Vec<RegexPattern>,
+
+    /// Regexes whose matching comments are removed even when a preservation
+    /// rule would keep them, unless they carry `~keep`
+    #[serde(default)]
+    pub remove_regex: Vec<RegexPattern>,
+
     pub use_default_ignores: Option<bool>,
+
+    pub only_commented_code: Option<bool>,
+
+    /// Parse matching files as this language instead of detecting it
+    pub language: Option<String>,
+}
+
+/// A `[patterns]` glob, matched against paths relative to the directory of the
+/// config file that declares it.
+#[derive(Debug)]
+struct PathPattern {
+    /// The config file declaring the pattern.
+    config_path: PathBuf,
+    base_dir: PathBuf,
+    matcher: glob::Pattern,
+    config: PatternConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -114,10 +189,292 @@ pub struct ResolvedConfig {
     pub remove_fixme: bool,
     pub remove_docs: bool,
     pub preserve_patterns: Vec<String>,
+    pub remove_patterns: Vec<String>,
+    pub preserve_regex: Vec<RegexPattern>,
+    pub remove_regex: Vec<RegexPattern>,
     pub use_default_ignores: bool,
+    pub only_commented_code: bool,
     pub respect_gitignore: bool,
     pub traverse_git_repos: bool,
+    /// Leave the file untouched; only ever set by an `uncomment: ignore-file`
+    /// directive in the file itself.
+    pub ignore_file: bool,
     pub language_config: Option<LanguageConfig>,
+    /// Language forced by `--language` or a `[patterns]` entry, bypassing detection.
+    pub language: Option<String>,
+    /// Lines outside which comments are kept; only ever set by CLI overrides.
+    pub line_filter: Option<LineFilter>,
+    /// Where each of the settings above came from.
+    pub sources: SettingSources,
+}
+
+impl ResolvedConfig {
+    /// Layer a `[languages]` or `[patterns]` table over these settings: the flags
+    /// it sets replace the current ones and its lists are added.
+    fn layer(&mut self, settings: TableSettings<'_>, source: SettingSource) {
+        if let Some(remove_todos) = settings.remove_todos {
+            self.remove_todos = remove_todos;
+            self.sources.remove_todos = source.clone();
+        }
+        if let Some(remove_fixme) = settings.remove_fixme {
+            self.remove_fixme = remove_fixme;
+            self.sources.remove_fixme = source.clone();
+        }
+        if let Some(remove_docs) = settings.remove_docs {
+            self.remove_docs = remove_docs;
+            self.sources.remove_docs = source.clone();
+        }
+        if let Some(use_default_ignores) = settings.use_default_ignores {
+            self.use_default_ignores = use_default_ignores;
+            self.sources.use_default_ignores = source.clone();
+        }
+        if let Some(only_commented_code) = settings.only_commented_code {
+            self.only_commented_code = only_commented_code;
+            self.sources.only_commented_code = source.clone();
+        }
+
+        let lists = settings.lists;
+        self.preserve_patterns.extend(lists.preserve_patterns.iter().cloned());
+        self.preserve_patterns.sort();
+        self.preserve_patterns.dedup();
+        extend_unique(&mut self.remove_patterns, lists.remove_patterns);
+        extend_unique(&mut self.preserve_regex, lists.preserve_regex);
+        extend_unique(&mut self.remove_regex, lists.remove_regex);
+        self.sources.record_lists(&lists, &source);
+    }
+}
+
+/// The settings `[languages]` and `[patterns]` tables share.
+struct TableSettings<'a> {
+    remove_todos: Option<bool>,
+    remove_fixme: Option<bool>,
+    remove_docs: Option<bool>,
+    use_default_ignores: Option<bool>,
+    only_commented_code: Option<bool>,
+    lists: ListSettings<'a>,
+}
+
+/// The pattern lists every config table has; layering adds to them.
+struct ListSettings<'a> {
+    preserve_patterns: &'a [String],
+    remove_patterns: &'a [String],
+    preserve_regex: &'a [RegexPattern],
+    remove_regex: &'a [RegexPattern],
+}
+
+impl<'a> ListSettings<'a> {
+    fn new(
+        preserve_patterns: &'a [String],
+        remove_patterns: &'a [String],
+        preserve_regex: &'a [RegexPattern],
+        remove_regex: &'a [RegexPattern],
+    ) -> Self {
+        Self {
+            preserve_patterns,
+            remove_patterns,
+            preserve_regex,
+            remove_regex,
+        }
+    }
+}
+
+impl<'a> From<&'a LanguageConfig> for TableSettings<'a> {
+    fn from(config: &'a LanguageConfig) -> Self {
+        Self {
+            remove_todos: config.remove_todos,
+            remove_fixme: config.remove_fixme,
+            remove_docs: config.remove_docs,
+            use_default_ignores: config.use_default_ignores,
+            only_commented_code: config.only_commented_code,
+            lists: ListSettings::new(
+                &config.preserve_patterns,
+                &config.remove_patterns,
+                &config.preserve_regex,
+                &config.remove_regex,
+            ),
+        }
+    }
+}
+
+impl<'a> From<&'a PatternConfig> for TableSettings<'a> {
+    fn from(config: &'a PatternConfig) -> Self {
+        Self {
+            remove_todos: config.remove_todos,
+            remove_fixme: config.remove_fixme,
+            remove_docs: config.remove_docs,
+            use_default_ignores: config.use_default_ignores,
+            only_commented_code: config.only_commented_code,
+            lists: ListSettings::new(
+                &config.preserve_patterns,
+                &config.remove_patterns,
+                &config.preserve_regex,
+                &config.remove_regex,
+            ),
+        }
+    }
+}
+
+/// Append the items of `items` not already in `target`, keeping their order.
+fn extend_unique<T: PartialEq + Clone>(target: &mut Vec<T>, items: &[T]) {
+    for item in items {
+        if !target.contains(item) {
+            target.push(item.clone());
+        }
+    }
+}
+
+/// Where an effective setting came from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SettingSource {
+    /// Not set anywhere; the built-in default applies.
+    #[default]
+    Default,
+    /// The `[global]` table of a config file.
+    File(PathBuf),
+    /// A `[languages.<name>]` table of a config file.
+    Language { file: PathBuf, language: String },
+    /// A `[patterns."<glob>"]` table of a config file.
+    Pattern { file: PathBuf, pattern: String },
+    /// A command-line flag.
+    Cli,
+    /// An `uncomment:` directive on a (1-based) line of the file itself.
+    Directive { file: PathBuf, line: usize },
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::Default => write!(f, "default"),
+            SettingSource::File(file) => write!(f, "{}", file.display()),
+            SettingSource::Language { file, language } => write!(f, "{} [languages.{language}]", file.display()),
+            SettingSource::Pattern { file, pattern } => write!(f, "{} [patterns.{pattern:?}]", file.display()),
+            SettingSource::Cli => write!(f, "command line"),
+            SettingSource::Directive { file, line } => write!(f, "uncomment: directive at {}:{line}", file.display()),
+        }
+    }
+}
+
+/// Provenance of every [`ResolvedConfig`] setting that config files can change.
+#[derive(Debug, Clone, Default)]
+pub struct SettingSources {
+    pub remove_todos: SettingSource,
+    pub remove_fixme: SettingSource,
+    pub remove_docs: SettingSource,
+    pub use_default_ignores: SettingSource,
+    pub only_commented_code: SettingSource,
+    pub respect_gitignore: SettingSource,
+    pub traverse_git_repos: SettingSource,
+    pub ignore_file: SettingSource,
+    /// Each `preserve_patterns` entry with one of the places that added it; a
+    /// pattern listed in several places appears once per place.
+    pub preserve_patterns: Vec<(String, SettingSource)>,
+    /// Each `remove_patterns` entry with one of the places that added it.
+    pub remove_patterns: Vec<(String, SettingSource)>,
+    /// Each `preserve_regex` entry with one of the places that added it.
+    pub preserve_regex: Vec<(RegexPattern, SettingSource)>,
+    /// Each `remove_regex` entry with one of the places that added it.
+    pub remove_regex: Vec<(RegexPattern, SettingSource)>,
+}
+
+impl SettingSources {
+    /// The places that added `pattern` to `preserve_patterns`.
+    pub fn preserve_pattern_sources<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a SettingSource> + 'a {
+        self.preserve_patterns
+            .iter()
+            .filter(move |(candidate, _)| candidate == pattern)
+            .map(|(_, source)| source)
+    }
+
+    /// The places that added `pattern` to `remove_patterns`.
+    pub fn remove_pattern_sources<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a SettingSource> + 'a {
+        self.remove_patterns
+            .iter()
+            .filter(move |(candidate, _)| candidate == pattern)
+            .map(|(_, source)| source)
+    }
+
+    /// The places that added `pattern` to `preserve_regex` or, with `removal`,
+    /// to `remove_regex`.
+    pub fn regex_sources<'a>(
+        &'a self,
+        pattern: &'a RegexPattern,
+        removal: bool,
+    ) -> impl Iterator<Item = &'a SettingSource> + 'a {
+        let entries = if removal {
+            &self.remove_regex
+        } else {
+            &self.preserve_regex
+        };
+        entries
+            .iter()
+            .filter(move |(candidate, _)| candidate == pattern)
+            .map(|(_, source)| source)
+    }
+
+    /// Note what merging `config`, loaded from `config_path`, set. Merging
+    /// replaces every `[global]` flag, so the file is the source of the flags
+    /// it writes (or, for a config built in code, changes from the default),
+    /// and the others are back to their defaults.
+    fn record_global(&mut self, config_path: &Path, config: &Config) {
+        let source = SettingSource::File(config_path.to_path_buf());
+        let global = &config.global;
+        let defaults = GlobalConfig::default();
+        let flag_source = |key: &str, value: bool, default: bool| {
+            if value != default || config.global_keys.iter().any(|written| written == key) {
+                source.clone()
+            } else {
+                SettingSource::Default
+            }
+        };
+        self.remove_todos = flag_source("remove_todos", global.remove_todos, defaults.remove_todos);
+        self.remove_fixme = flag_source("remove_fixme", global.remove_fixme, defaults.remove_fixme);
+        self.remove_docs = flag_source("remove_docs", global.remove_docs, defaults.remove_docs);
+        self.use_default_ignores = flag_source(
+            "use_default_ignores",
+            global.use_default_ignores,
+            defaults.use_default_ignores,
+        );
+        self.only_commented_code = flag_source(
+            "only_commented_code",
+            global.only_commented_code,
+            defaults.only_commented_code,
+        );
+        self.respect_gitignore = flag_source(
+            "respect_gitignore",
+            global.respect_gitignore,
+            defaults.respect_gitignore,
+        );
+        self.traverse_git_repos = flag_source(
+            "traverse_git_repos",
+            global.traverse_git_repos,
+            defaults.traverse_git_repos,
+        );
+        self.record_lists(
+            &ListSettings::new(
+                &global.preserve_patterns,
+                &global.remove_patterns,
+                &global.preserve_regex,
+                &global.remove_regex,
+            ),
+            &source,
+        );
+    }
+
+    fn record_lists(&mut self, lists: &ListSettings<'_>, source: &SettingSource) {
+        self.preserve_patterns.extend(
+            lists
+                .preserve_patterns
+                .iter()
+                .cloned()
+                .map(|item| (item, source.clone())),
+        );
+        self.remove_patterns
+            .extend(lists.remove_patterns.iter().cloned().map(|item| (item, source.clone())));
+        self.preserve_regex
+            .extend(lists.preserve_regex.iter().cloned().map(|item| (item, source.clone())));
+        self.remove_regex
+            .extend(lists.remove_regex.iter().cloned().map(|item| (item, source.clone())));
+    }
 }
 
 #[derive(Debug)]
@@ -126,6 +483,9 @@ pub struct ConfigManager {
 
     path_configs: AHashMap<PathBuf, ResolvedConfig>,
 
+    /// Every `[patterns]` entry, from lowest to highest precedence.
+    path_patterns: Vec<PathPattern>,
+
     root_dir: PathBuf,
 }
 
@@ -143,7 +503,11 @@ impl Default for GlobalConfig {
             remove_fixme: false,
             remove_docs: false,
             preserve_patterns: Vec::new(),
+            remove_patterns: Vec::new(),
+            preserve_regex: Vec::new(),
+            remove_regex: Vec::new(),
             use_default_ignores: true,
+            only_commented_code: false,
             respect_gitignore: true,
             traverse_git_repos: false,
         }
@@ -155,8 +519,12 @@ impl Config {
         let content = std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))?;
 
-        let config: Config = toml::from_str(&content)
+        let mut config: Config = toml::from_str(&content)
             .with_context(|| format!("Failed to parse config file: {}", path.as_ref().display()))?;
+        config.global_keys = toml::from_str::<toml::Table>(&content)
+            .ok()
+            .and_then(|table| Some(table.get("global")?.as_table()?.keys().cloned().collect()))
+            .unwrap_or_default();
 
         config
             .validate()
@@ -180,6 +548,10 @@ impl Config {
             }
         }
 
+        for pattern in self.patterns.keys() {
+            glob::Pattern::new(pattern).with_context(|| format!("Invalid glob in [patterns.\"{pattern}\"]"))?;
+        }
+
         Ok(())
     }
 
@@ -247,7 +619,12 @@ preserve_patterns = [       # Additional patterns to preserve
     "WORKAROUND",
     "NOTE"
 ]
+remove_patterns = []        # Remove matching comments even if kept above (unless ~keep)
+# Regular expressions, optionally matched as whole words or ignoring case
+# preserve_regex = ['TODO\(#\d+\)', { pattern = "safety", whole_word = true, case_insensitive = true }]
+# remove_regex = ['@author\b']
 use_default_ignores = true  # Use built-in ignore patterns
+only_commented_code = false # Remove only commented-out code, keep prose
 respect_gitignore = true    # Respect .gitignore files
 traverse_git_repos = false # Traverse into nested git repos
 
@@ -308,6 +685,7 @@ preserve_patterns = ["MARK:", "TODO:", "FIXME:", "swiftlint:"]
 # comment_nodes = ["comment"]
 #
 # Pattern-based rules for specific file patterns
+# When several globs match a file, the one declared last wins.
 [patterns."tests/**/*.py"]
 # Apply different rules to test files
 remove_docs = true
@@ -323,6 +701,9 @@ remove_todos = true
 remove_docs = true
 remove_todos = true
 preserve_patterns = []
+
+# [patterns."**/*.inc"]
+# language = "php"  # Parse matching files as this language, whatever the extension
 "#
         .to_string()
     }
@@ -601,6 +982,7 @@ preserve_patterns = ["clippy:", "allow", "deny", "warn"]
 remove_docs = false  # Keep doc comments by default
 
 # Pattern-based rules for different file types
+# When several globs match a file, the one declared last wins.
 [patterns."tests/**/*.py"]
 # More aggressive with test files
 remove_docs = true
@@ -1449,6 +1831,7 @@ comment_nodes = ["comment"]"#,
         merged.global.remove_fixme = other.global.remove_fixme;
         merged.global.remove_docs = other.global.remove_docs;
         merged.global.use_default_ignores = other.global.use_default_ignores;
+        merged.global.only_commented_code = other.global.only_commented_code;
         merged.global.respect_gitignore = other.global.respect_gitignore;
         merged.global.traverse_git_repos = other.global.traverse_git_repos;
 
@@ -1457,6 +1840,9 @@ comment_nodes = ["comment"]"#,
         patterns.sort();
         patterns.dedup();
         merged.global.preserve_patterns = patterns;
+        extend_unique(&mut merged.global.remove_patterns, &other.global.remove_patterns);
+        extend_unique(&mut merged.global.preserve_regex, &other.global.preserve_regex);
+        extend_unique(&mut merged.global.remove_regex, &other.global.remove_regex);
 
         merged.languages.extend(
             other
@@ -1480,9 +1866,11 @@ impl ConfigManager {
         let root_dir = root_dir.as_ref().to_path_buf();
         let configs = Self::discover_configs(&root_dir)?;
 
+        let path_patterns = Self::compile_patterns(&configs, &root_dir);
         let mut manager = Self {
             configs,
             path_configs: AHashMap::new(),
+            path_patterns,
             root_dir,
         };
 
@@ -1494,9 +1882,11 @@ impl ConfigManager {
         let root_dir = root_dir.as_ref().to_path_buf();
         let configs = vec![(root_dir.clone(), config)];
 
+        let path_patterns = Self::compile_patterns(&configs, &root_dir);
         let mut manager = Self {
             configs,
             path_configs: AHashMap::new(),
+            path_patterns,
             root_dir,
         };
 
@@ -1504,6 +1894,19 @@ impl ConfigManager {
         Ok(manager)
     }
 
+    /// The manager for a run from `root_dir`: just `config_file` when one is
+    /// given with `--config`, otherwise the configs discovered under `root_dir`.
+    pub fn load<P: AsRef<Path>>(root_dir: P, config_file: Option<&Path>) -> Result<Self> {
+        match config_file {
+            Some(config_path) => {
+                let config = Config::from_file(config_path)
+                    .with_context(|| format!("Failed to load config file: {}", config_path.display()))?;
+                Self::from_single_config(root_dir, config)
+            }
+            None => Self::new(root_dir).context("Failed to initialize configuration manager"),
+        }
+    }
+
     fn discover_configs(root_dir: &Path) -> Result<Vec<(PathBuf, Config)>> {
         let mut configs = Vec::new();
 
@@ -1520,7 +1923,7 @@ impl ConfigManager {
                             configs.push((path.to_path_buf(), config));
                         }
                         Err(e) => {
-                            eprintln!("Warning: Failed to load config file {}: {e}", path.display());
+                            eprintln!("Warning: Failed to load config file {}: {e:#}", path.display());
                         }
                     }
                 }
@@ -1545,6 +1948,39 @@ impl ConfigManager {
         Ok(configs)
     }
 
+    /// Compile every config's `[patterns]` keys. The global config comes first and
+    /// deeper configs later, so nearer files win; within one config, entries keep
+    /// their declaration order, so the last matching one wins.
+    fn compile_patterns(configs: &[(PathBuf, Config)], root_dir: &Path) -> Vec<PathPattern> {
+        let global_config_path = Self::global_config_path();
+        let mut ordered: Vec<&(PathBuf, Config)> = configs.iter().collect();
+        ordered.sort_by_key(|(config_path, _)| global_config_path.as_ref() != Some(config_path));
+
+        let mut compiled = Vec::new();
+        for (config_path, config) in ordered {
+            // The global config and a single `--config` file (keyed by the root
+            // directory itself) are anchored at the project root.
+            let base_dir = if global_config_path.as_ref() == Some(config_path) || config_path == root_dir {
+                root_dir
+            } else {
+                config_path.parent().unwrap_or(root_dir)
+            };
+
+            for (key, pattern_config) in &config.patterns {
+                match glob::Pattern::new(key) {
+                    Ok(matcher) => compiled.push(PathPattern {
+                        config_path: config_path.clone(),
+                        base_dir: base_dir.to_path_buf(),
+                        matcher,
+                        config: pattern_config.clone(),
+                    }),
+                    Err(e) => eprintln!("Warning: Ignoring invalid pattern \"{key}\": {e}"),
+                }
+            }
+        }
+        compiled
+    }
+
     fn global_config_path() -> Option<PathBuf> {
         dirs::config_dir().map(|dir| dir.join("uncomment").join("config.toml"))
     }
@@ -1567,17 +2003,17 @@ impl ConfigManager {
         Ok(())
     }
 
-    fn resolve_config_for_path(&self, path: &Path) -> ResolvedConfig {
-        let mut base_config = Config::default();
+    /// The configs that apply to files in directory `path`, from lowest to
+    /// highest precedence: the global config, then from the root down.
+    fn configs_for_dir(&self, path: &Path) -> Vec<(&PathBuf, &Config)> {
         let global_config_path = Self::global_config_path();
-
-        if let Some((_, global_config)) = self
+        let mut configs: Vec<(&PathBuf, &Config)> = self
             .configs
             .iter()
             .find(|(config_path, _)| global_config_path.as_ref() == Some(config_path))
-        {
-            base_config = base_config.merge_with(global_config);
-        }
+            .map(|(config_path, config)| (config_path, config))
+            .into_iter()
+            .collect();
 
         let mut current_path = path;
         let mut applicable_configs = Vec::new();
@@ -1587,7 +2023,7 @@ impl ConfigManager {
                 if let Some(config_dir) = config_path.parent()
                     && config_dir == current_path
                 {
-                    applicable_configs.push(config);
+                    applicable_configs.push((config_path, config));
                 }
             }
 
@@ -1599,8 +2035,17 @@ impl ConfigManager {
         }
 
         applicable_configs.reverse();
-        for config in applicable_configs {
+        configs.extend(applicable_configs);
+        configs
+    }
+
+    fn resolve_config_for_path(&self, path: &Path) -> ResolvedConfig {
+        let mut base_config = Config::default();
+        let mut sources = SettingSources::default();
+
+        for (config_path, config) in self.configs_for_dir(path) {
             base_config = base_config.merge_with(config);
+            sources.record_global(config_path, config);
         }
 
         ResolvedConfig {
@@ -1608,16 +2053,91 @@ impl ConfigManager {
             remove_fixme: base_config.global.remove_fixme,
             remove_docs: base_config.global.remove_docs,
             preserve_patterns: base_config.global.preserve_patterns,
+            remove_patterns: base_config.global.remove_patterns,
+            preserve_regex: base_config.global.preserve_regex,
+            remove_regex: base_config.global.remove_regex,
             use_default_ignores: base_config.global.use_default_ignores,
+            only_commented_code: base_config.global.only_commented_code,
             respect_gitignore: base_config.global.respect_gitignore,
             traverse_git_repos: base_config.global.traverse_git_repos,
+            ignore_file: false,
             language_config: None,
+            language: None,
+            line_filter: None,
+            sources,
         }
     }
 
+    /// The `[patterns]` entries matching `file_path`, from lowest to highest precedence.
+    pub fn matching_patterns<'a>(&'a self, file_path: &Path) -> impl Iterator<Item = &'a PatternConfig> + 'a {
+        self.matching_path_patterns(file_path).map(|pattern| &pattern.config)
+    }
+
+    fn matching_path_patterns<'a>(&'a self, file_path: &Path) -> impl Iterator<Item = &'a PathPattern> + 'a {
+        let absolute_file_path = if file_path.is_absolute() {
+            file_path.to_path_buf()
+        } else {
+            std::env::current_dir().unwrap_or_default().join(file_path)
+        };
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+
+        self.path_patterns.iter().filter(move |pattern| {
+            absolute_file_path
+                .strip_prefix(&pattern.base_dir)
+                .is_ok_and(|relative| pattern.matcher.matches_path_with(relative, options))
+        })
+    }
+
+    /// Layer the settings of every `[patterns]` entry matching `file_path` over
+    /// `config`; later entries win.
+    fn apply_patterns(&self, file_path: &Path, config: &mut ResolvedConfig) {
+        for pattern in self.matching_path_patterns(file_path) {
+            let source = SettingSource::Pattern {
+                file: pattern.config_path.clone(),
+                pattern: pattern.matcher.as_str().to_string(),
+            };
+            config.layer(TableSettings::from(&pattern.config), source);
+        }
+    }
+
+    /// The language a `[patterns]` entry forces for `file_path`, if any.
+    pub fn language_for_path(&self, file_path: &Path) -> Option<String> {
+        self.matching_patterns(file_path)
+            .filter_map(|pattern| pattern.language.clone())
+            .last()
+    }
+
+    /// The config files whose `[global]` settings apply to `file_path`, from
+    /// lowest to highest precedence.
+    pub fn config_files_for<P: AsRef<Path>>(&self, file_path: P) -> Vec<&Path> {
+        let file_path = file_path.as_ref();
+        let absolute_file_path = if file_path.is_absolute() {
+            file_path.to_path_buf()
+        } else {
+            std::env::current_dir().unwrap_or_default().join(file_path)
+        };
+        let dir_path = absolute_file_path.parent().unwrap_or(&absolute_file_path);
+
+        self.configs_for_dir(dir_path)
+            .into_iter()
+            .map(|(config_path, _)| config_path.as_path())
+            .collect()
+    }
+
+    /// The settings for `file_path`: its directory's config files, then any
+    /// matching `[patterns]` entries.
     pub fn get_config_for_file<P: AsRef<Path>>(&self, file_path: P) -> ResolvedConfig {
         let file_path = file_path.as_ref();
+        let mut config = self.get_config_for_dir_of(file_path);
+        self.apply_patterns(file_path, &mut config);
+        config
+    }
 
+    /// The settings from the config files that apply to `file_path`'s directory.
+    fn get_config_for_dir_of(&self, file_path: &Path) -> ResolvedConfig {
         let absolute_file_path = if file_path.is_absolute() {
             file_path.to_path_buf()
         } else {
@@ -1632,43 +2152,46 @@ impl ConfigManager {
             .unwrap_or_else(|| self.resolve_config_for_path(dir_path))
     }
 
+    /// The settings for `file_path` parsed as `language_name`. Precedence, from
+    /// lowest to highest: config files, the `[languages]` entry, then matching
+    /// `[patterns]` entries.
     pub fn get_config_for_file_with_language<P: AsRef<Path>>(
         &self,
         file_path: P,
         language_name: &str,
     ) -> ResolvedConfig {
-        let mut config = self.get_config_for_file(file_path);
-
-        if let Some(lang_config) = self.get_language_config(language_name) {
-            if let Some(remove_todos) = lang_config.remove_todos {
-                config.remove_todos = remove_todos;
-            }
-            if let Some(remove_fixme) = lang_config.remove_fixme {
-                config.remove_fixme = remove_fixme;
-            }
-            if let Some(remove_docs) = lang_config.remove_docs {
-                config.remove_docs = remove_docs;
-            }
-            if let Some(use_default_ignores) = lang_config.use_default_ignores {
-                config.use_default_ignores = use_default_ignores;
-            }
-
-            config
-                .preserve_patterns
-                .extend(lang_config.preserve_patterns.iter().cloned());
-            config.preserve_patterns.sort();
-            config.preserve_patterns.dedup();
+        let file_path = file_path.as_ref();
+        let mut config = self.get_config_for_dir_of(file_path);
 
-            config.language_config = Some(lang_config);
+        if let Some((config_path, lang_config)) = self.find_language_config(language_name) {
+            let source = SettingSource::Language {
+                file: config_path.clone(),
+                language: language_name.to_string(),
+            };
+            config.layer(TableSettings::from(lang_config), source);
+            config.language_config = Some(lang_config.clone());
         }
 
+        self.apply_patterns(file_path, &mut config);
         config
     }
 
     pub fn get_language_config(&self, language_name: &str) -> Option<LanguageConfig> {
-        for (_, config) in self.configs.iter().rev() {
+        self.find_language_config(language_name)
+            .map(|(_, lang_config)| lang_config.clone())
+    }
+
+    /// The config file declaring the `[languages]` entry for `language_name`.
+    pub fn language_config_file(&self, language_name: &str) -> Option<&Path> {
+        self.find_language_config(language_name)
+            .map(|(config_path, _)| config_path.as_path())
+    }
+
+    /// The `[languages]` entry for `language_name` and the config file declaring it.
+    fn find_language_config(&self, language_name: &str) -> Option<(&PathBuf, &LanguageConfig)> {
+        for (config_path, config) in self.configs.iter().rev() {
             if let Some(lang_config) = config.languages.get(language_name) {
-                return Some(lang_config.clone());
+                return Some((config_path, lang_config));
             }
 
             if let Some((_, lang_config)) = config
@@ -1676,7 +2199,7 @@ impl ConfigManager {
                 .iter()
                 .find(|(key, _)| key.eq_ignore_ascii_case(language_name))
             {
-                return Some(lang_config.clone());
+                return Some((config_path, lang_config));
             }
         }
         None
@@ -1721,10 +2244,14 @@ mod tests {
                 comment_nodes: vec!["comment".to_string()],
                 doc_comment_nodes: vec![],
                 preserve_patterns: vec![],
+                remove_patterns: vec![],
+                preserve_regex: vec![],
+                remove_regex: vec![],
                 remove_todos: None,
                 remove_fixme: None,
                 remove_docs: None,
                 use_default_ignores: None,
+                only_commented_code: None,
             },
         );
 
@@ -1755,4 +2282,112 @@ mod tests {
         assert!(merged.global.remove_todos);
         assert_eq!(merged.global.preserve_patterns, vec!["FIXME", "TODO"]);
     }
+
+    #[test]
+    fn test_resolved_settings_record_their_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("package");
+        std::fs::create_dir(&package).unwrap();
+        let root_config = dir.path().join(".uncommentrc.toml");
+        let package_config = package.join(".uncommentrc.toml");
+        std::fs::write(&root_config, "[global]\npreserve_patterns = [\"SAFETY\"]\n").unwrap();
+        std::fs::write(
+            &package_config,
+            "[global]\nremove_todos = true\n\n[languages.rust]\nname = \"Rust\"\nextensions = [\".rs\"]\n\
+             comment_nodes = [\"line_comment\"]\nremove_docs = true\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::new(dir.path()).unwrap();
+        let file = package.join("lib.rs");
+        assert_eq!(
+            manager.config_files_for(&file),
+            [root_config.as_path(), package_config.as_path()]
+        );
+
+        let config = manager.get_config_for_file_with_language(&file, "rust");
+        assert_eq!(config.sources.remove_todos, SettingSource::File(package_config.clone()));
+        // Flags a file leaves out keep their defaults and are not its doing.
+        assert_eq!(config.sources.remove_fixme, SettingSource::Default);
+        assert_eq!(config.sources.respect_gitignore, SettingSource::Default);
+        assert_eq!(
+            config.sources.remove_docs,
+            SettingSource::Language {
+                file: package_config,
+                language: "rust".to_string()
+            }
+        );
+        assert_eq!(
+            config.sources.preserve_pattern_sources("SAFETY").collect::<Vec<_>>(),
+            [&SettingSource::File(root_config.clone())]
+        );
+        assert_eq!(
+            manager.config_files_for(dir.path().join("lib.rs")),
+            [root_config.as_path()]
+        );
+        let config = manager.get_config_for_file_with_language(dir.path().join("lib.rs"), "rust");
+        assert_eq!(config.sources.remove_todos, SettingSource::Default);
+    }
+
+    #[test]
+    fn test_patterns_override_language_and_global_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".uncommentrc.toml");
+        std::fs::write(
+            &config_path,
+            "[global]\nremove_docs = true\n\n\
+             [languages.rust]\nname = \"Rust\"\nextensions = [\".rs\"]\ncomment_nodes = [\"line_comment\"]\nremove_todos = true\n\n\
+             [patterns.\"tests/**\"]\nremove_docs = false\nremove_todos = false\npreserve_patterns = [\"SAFETY\"]\n",
+        )
+        .unwrap();
+        let manager = ConfigManager::new(dir.path()).unwrap();
+
+        let test_file = dir.path().join("tests/unit/a.rs");
+        let config = manager.get_config_for_file_with_language(&test_file, "rust");
+        assert!(!config.remove_docs);
+        assert!(!config.remove_todos);
+        assert_eq!(config.preserve_patterns, ["SAFETY"]);
+        assert_eq!(
+            config.sources.remove_todos,
+            SettingSource::Pattern {
+                file: config_path.clone(),
+                pattern: "tests/**".to_string()
+            }
+        );
+        assert!(!manager.get_config_for_file(&test_file).remove_docs);
+
+        let src_file = dir.path().join("src/a.rs");
+        let config = manager.get_config_for_file_with_language(&src_file, "rust");
+        assert!(config.remove_docs);
+        assert!(config.remove_todos);
+        assert!(config.preserve_patterns.is_empty());
+    }
+
+    #[test]
+    fn test_patterns_match_relative_to_declaring_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("package");
+        std::fs::create_dir(&package).unwrap();
+        std::fs::write(
+            package.join(".uncommentrc.toml"),
+            "[patterns.\"lib/*.inc\"]\nlanguage = \"c\"\n\n[patterns.\"**/*.inc\"]\nlanguage = \"php\"\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::new(dir.path()).unwrap();
+
+        // Both globs match; the one declared last wins, whatever its length or
+        // sort order.
+        assert_eq!(
+            manager.language_for_path(&package.join("lib/b.inc")).as_deref(),
+            Some("php")
+        );
+        assert_eq!(manager.matching_patterns(&package.join("lib/b.inc")).count(), 2);
+        assert_eq!(
+            manager.language_for_path(&package.join("a.inc")).as_deref(),
+            Some("php")
+        );
+        assert_eq!(manager.matching_patterns(&package.join("a.inc")).count(), 1);
+        assert_eq!(manager.language_for_path(&dir.path().join("a.inc")), None);
+    }
 }
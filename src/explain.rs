@@ -0,0 +1,307 @@
+//! Why each comment in a file is kept or removed (`uncomment explain`).
+//!
+//! [`Processor::explain`](crate::processor::Processor::explain) runs the visitor
+//! exactly as a normal run does and keeps every comment together with its
+//! [`Preservation`]. This module turns that into a [`Decision`] naming the rule
+//! that decided, and the config setting and file(s) that put the rule in play.
+
+use crate::ast::visitor::{CommentInfo, Preservation};
+use crate::commented_code::CommentKind;
+use crate::config::{ResolvedConfig, SettingSource};
+use crate::processor::{RuleOrigin, comment_lines};
+use crate::rules::preservation::PreservationRule;
+use std::path::{Path, PathBuf};
+
+/// Every comment of one file with the context needed to explain it.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub path: PathBuf,
+    pub language: String,
+    /// Name of the language-specific handler, or `None` for the default one.
+    pub handler: Option<&'static str>,
+    pub content: String,
+    /// Every comment found, in source order.
+    pub comments: Vec<CommentInfo>,
+    /// Whether each of `comments` is commented-out code or prose.
+    pub kinds: Vec<CommentKind>,
+    /// The preservation rules the comments were checked against, in order.
+    pub rules: Vec<PreservationRule>,
+    /// The setting that put each of `rules` in the set.
+    pub rule_origins: Vec<RuleOrigin>,
+    /// The `remove_patterns` and `remove_regex` rules, which override `rules`.
+    pub removal_rules: Vec<PreservationRule>,
+    pub config: ResolvedConfig,
+}
+
+/// A config setting behind a decision, and where it was set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting<'a> {
+    /// The setting as written in a config file, e.g. `remove_todos = false`.
+    pub assignment: String,
+    pub sources: Vec<&'a SettingSource>,
+}
+
+/// The fate of one comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision<'a> {
+    pub comment: &'a CommentInfo,
+    /// 1-based first and last line of the comment.
+    pub lines: (usize, usize),
+    pub kind: CommentKind,
+    pub kept: bool,
+    /// What decided, e.g. `pattern "TODO"`.
+    pub reason: String,
+    /// The setting that made the deciding rule apply, or for a removed comment
+    /// the one that switched off a rule that would have kept it.
+    pub setting: Option<Setting<'a>>,
+}
+
+impl Explanation {
+    /// One decision per comment, limited to comments spanning `line` when given.
+    pub fn decisions(&self, line: Option<usize>) -> Vec<Decision<'_>> {
+        self.comments
+            .iter()
+            .zip(&self.kinds)
+            .map(|(comment, kind)| (comment, *kind, comment_lines(comment, &self.content)))
+            .filter(|(_, _, (first, last))| line.is_none_or(|line| (*first..=*last).contains(&line)))
+            .map(|(comment, kind, lines)| self.decide(comment, kind, lines))
+            .collect()
+    }
+
+    fn decide<'a>(&'a self, comment: &'a CommentInfo, kind: CommentKind, lines: (usize, usize)) -> Decision<'a> {
+        let keeps_prose = self.config.only_commented_code && kind == CommentKind::Prose;
+        let (reason, setting) = match comment.preserved_by {
+            _ if self.config.ignore_file => (
+                "the file is ignored".to_string(),
+                Some(Setting {
+                    assignment: "uncomment: ignore-file".to_string(),
+                    sources: vec![&self.config.sources.ignore_file],
+                }),
+            ),
+            None if keeps_prose => (
+                "prose; only commented-out code is removed".to_string(),
+                Some(setting(
+                    "only_commented_code",
+                    true,
+                    &self.config.sources.only_commented_code,
+                )),
+            ),
+            Some(Preservation::Handler) => (
+                format!(
+                    "{} handler keeps it (should_preserve_comment)",
+                    self.handler.unwrap_or("default")
+                ),
+                None,
+            ),
+            Some(Preservation::KeepBlock) => ("~keep on another line of this comment block".to_string(), None),
+            Some(Preservation::Region) => ("inside an uncomment:off ... uncomment:on region".to_string(), None),
+            Some(Preservation::KeepNext) => ("uncomment:keep-next directive".to_string(), None),
+            Some(Preservation::FileDirective) => ("holds a file-level uncomment: directive".to_string(), None),
+            Some(Preservation::Rule(index)) => {
+                let rule = &self.rules[index];
+                (
+                    rule.to_string(),
+                    rule_setting(rule, self.rule_origins[index], &self.config),
+                )
+            }
+            None => match comment.removed_by {
+                Some(index) => {
+                    let rule = &self.removal_rules[index];
+                    (format!("removal rule {rule}"), list_setting(rule, true, &self.config))
+                }
+                None => (
+                    "no preservation rule matches".to_string(),
+                    self.disabled_setting(comment),
+                ),
+            },
+        };
+
+        Decision {
+            comment,
+            lines,
+            kind,
+            kept: comment.should_preserve || keeps_prose || self.config.ignore_file,
+            reason,
+            setting,
+        }
+    }
+
+    /// The setting that turned off a rule that would otherwise have kept `comment`.
+    fn disabled_setting(&self, comment: &CommentInfo) -> Option<Setting<'_>> {
+        let config = &self.config;
+        let sources = &config.sources;
+        let text = comment.content(&self.content);
+
+        if config.remove_todos && (text.contains("TODO") || text.contains("todo")) {
+            return Some(setting("remove_todos", true, &sources.remove_todos));
+        }
+        if config.remove_fixme && (text.contains("FIXME") || text.contains("fixme")) {
+            return Some(setting("remove_fixme", true, &sources.remove_fixme));
+        }
+        if config.remove_docs && PreservationRule::documentation().matches(comment, text) {
+            return Some(setting("remove_docs", true, &sources.remove_docs));
+        }
+        if !config.use_default_ignores
+            && PreservationRule::comprehensive_rules()
+                .iter()
+                .any(|rule| rule.matches(comment, text))
+        {
+            return Some(setting("use_default_ignores", false, &sources.use_default_ignores));
+        }
+        None
+    }
+}
+
+/// The setting behind a rule of origin `origin`; `None` for rules that are always on.
+pub fn rule_setting<'a>(
+    rule: &'a PreservationRule,
+    origin: RuleOrigin,
+    config: &'a ResolvedConfig,
+) -> Option<Setting<'a>> {
+    let sources = &config.sources;
+    match origin {
+        RuleOrigin::Always => None,
+        RuleOrigin::KeepTodos => Some(setting("remove_todos", false, &sources.remove_todos)),
+        RuleOrigin::KeepFixme => Some(setting("remove_fixme", false, &sources.remove_fixme)),
+        RuleOrigin::KeepDocs => Some(setting("remove_docs", false, &sources.remove_docs)),
+        RuleOrigin::PreservePattern | RuleOrigin::PreserveRegex => list_setting(rule, false, config),
+        RuleOrigin::DefaultIgnores => Some(setting("use_default_ignores", true, &sources.use_default_ignores)),
+    }
+}
+
+/// The `preserve_patterns` or `preserve_regex` entry behind `rule` or, with
+/// `removal`, the `remove_patterns` or `remove_regex` one.
+pub fn list_setting<'a>(rule: &'a PreservationRule, removal: bool, config: &'a ResolvedConfig) -> Option<Setting<'a>> {
+    let sources = &config.sources;
+    match rule {
+        PreservationRule::Pattern(pattern) if removal => Some(Setting {
+            assignment: format!("remove_patterns += {pattern:?}"),
+            sources: sources.remove_pattern_sources(pattern).collect(),
+        }),
+        PreservationRule::Pattern(pattern) => Some(Setting {
+            assignment: format!("preserve_patterns += {pattern:?}"),
+            sources: sources.preserve_pattern_sources(pattern).collect(),
+        }),
+        PreservationRule::Regex(regex) => {
+            let key = if removal { "remove_regex" } else { "preserve_regex" };
+            Some(Setting {
+                assignment: format!("{key} += {regex}"),
+                sources: sources.regex_sources(regex, removal).collect(),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn setting<'a>(key: &str, value: bool, source: &'a SettingSource) -> Setting<'a> {
+    Setting {
+        assignment: format!("{key} = {value}"),
+        sources: vec![source],
+    }
+}
+
+/// Split the `FILE[:LINE]` argument of `uncomment explain`. A path that exists
+/// as written is taken whole, so file names containing `:` still work.
+pub fn parse_target(target: &str) -> Result<(PathBuf, Option<usize>), String> {
+    if Path::new(target).exists() {
+        return Ok((PathBuf::from(target), None));
+    }
+
+    match target.rsplit_once(':') {
+        Some((path, line)) if !path.is_empty() && !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit()) => {
+            match line.parse::<usize>() {
+                Ok(0) => Err("line numbers start at 1".to_string()),
+                Ok(line) => Ok((PathBuf::from(path), Some(line))),
+                Err(_) => Err(format!("`{line}` is not a line number")),
+            }
+        }
+        _ => Ok((PathBuf::from(target), None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ConfigManager};
+    use crate::processor::Processor;
+    use crate::rules::preservation::RegexPattern;
+
+    #[test]
+    fn splits_a_trailing_line_number() {
+        assert_eq!(
+            parse_target("src/main.rs:12"),
+            Ok((PathBuf::from("src/main.rs"), Some(12)))
+        );
+        assert_eq!(parse_target("src/main.rs"), Ok((PathBuf::from("src/main.rs"), None)));
+        assert_eq!(parse_target("c:\\x.rs"), Ok((PathBuf::from("c:\\x.rs"), None)));
+        assert!(parse_target("main.rs:0").is_err());
+    }
+
+    #[test]
+    fn names_the_deciding_rule_and_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.global.remove_fixme = true;
+        config.global.preserve_patterns = vec!["SAFETY".to_string()];
+        let config_manager = ConfigManager::from_single_config(dir.path(), config).unwrap();
+
+        let source = "// TODO: keep\n// SAFETY: checked\n// FIXME: go\n// note\n\n// one\n// two ~keep\nfn f() {}\n";
+        let path = dir.path().join("lib.rs");
+        let mut processor = Processor::new_with_config(&config_manager);
+        let explanation = processor
+            .explain(&path, source.to_string(), &config_manager, None)
+            .unwrap();
+        let decisions = explanation.decisions(None);
+
+        let summary: Vec<(usize, bool, &str)> = decisions
+            .iter()
+            .map(|decision| (decision.lines.0, decision.kept, decision.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, true, "pattern \"TODO\""),
+                (2, true, "pattern \"SAFETY\""),
+                (3, false, "no preservation rule matches"),
+                (4, false, "no preservation rule matches"),
+                (6, true, "~keep on another line of this comment block"),
+                (7, true, "pattern \"~keep\""),
+            ]
+        );
+
+        let config_file = SettingSource::File(dir.path().to_path_buf());
+        let todo = decisions[0].setting.as_ref().unwrap();
+        assert_eq!(todo.assignment, "remove_todos = false");
+        assert_eq!(todo.sources, [&SettingSource::Default]);
+        let safety = decisions[1].setting.as_ref().unwrap();
+        assert_eq!(safety.assignment, "preserve_patterns += \"SAFETY\"");
+        let fixme = decisions[2].setting.as_ref().unwrap();
+        assert_eq!(fixme.assignment, "remove_fixme = true");
+        assert_eq!(fixme.sources, [&config_file]);
+
+        assert_eq!(explanation.decisions(Some(3)).len(), 1);
+    }
+
+    #[test]
+    fn names_the_removal_rule_that_overrode_preservation() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.global.remove_regex = vec![RegexPattern::new("@author", false, false).unwrap()];
+        let config_manager = ConfigManager::from_single_config(dir.path(), config).unwrap();
+
+        let source = "/// @author someone\n/// @author me ~keep\nfn f() {}\n";
+        let path = dir.path().join("lib.rs");
+        let mut processor = Processor::new_with_config(&config_manager);
+        let explanation = processor
+            .explain(&path, source.to_string(), &config_manager, None)
+            .unwrap();
+        let decisions = explanation.decisions(Some(1));
+
+        assert!(!decisions[0].kept);
+        assert_eq!(decisions[0].reason, "removal rule regex /@author/");
+        let setting = decisions[0].setting.as_ref().unwrap();
+        assert_eq!(setting.assignment, "remove_regex += /@author/");
+        assert_eq!(setting.sources, [&SettingSource::File(dir.path().to_path_buf())]);
+        assert!(explanation.decisions(Some(2)).iter().all(|decision| decision.kept));
+    }
+}
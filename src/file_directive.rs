@@ -0,0 +1,191 @@
+//! File-level `uncomment:` directives, which pin behaviour next to the code
+//! instead of in a `[patterns]` entry of a central config:
+//!
+//! ```text
+//! # uncomment: ignore-file
+//! // uncomment: remove-docs=true, remove-todos=false
+//! ```
+//!
+//! They are read from the first [`DIRECTIVE_LINES`] lines, like modelines, on
+//! lines that start like a comment in any syntax and with `uncomment:` right
+//! after the comment marker, so strings and URLs that mention it do not count.
+//! Region directives such as `uncomment:off` are left to the visitor.
+
+use crate::commented_code::comment_line_body;
+use crate::config::{ResolvedConfig, SettingSource};
+use anyhow::{Result, bail};
+use std::path::Path;
+
+/// How many leading lines are searched for directives, enough to get past a
+/// license header's first lines or a shebang.
+pub const DIRECTIVE_LINES: usize = 10;
+
+const PREFIX: &str = "uncomment:";
+
+/// The settings a directive can change, by their kebab-case directive names.
+const SETTINGS: &[&str] = &[
+    "remove-todos",
+    "remove-fixme",
+    "remove-docs",
+    "use-default-ignores",
+    "only-commented-code",
+];
+
+/// The directives found at the top of one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDirectives {
+    /// 1-based line of an `ignore-file` directive.
+    pub ignore_file: Option<usize>,
+    /// Each setting as its directive name, its value and its 1-based line, in
+    /// file order; a later entry for the same setting wins.
+    pub settings: Vec<(&'static str, bool, usize)>,
+    /// Why each malformed directive was skipped, naming its line.
+    pub warnings: Vec<String>,
+}
+
+impl FileDirectives {
+    /// Find the directives in the first [`DIRECTIVE_LINES`] lines of `content`.
+    /// A settings directive naming an unknown setting, or a value other than
+    /// `true` or `false`, is skipped whole with a warning rather than applied
+    /// in part. A comma followed by anything but `setting=value` ends the list,
+    /// so prose may follow it.
+    pub fn parse(content: &str) -> Self {
+        let mut directives = Self::default();
+        for (index, line) in content.lines().take(DIRECTIVE_LINES).enumerate() {
+            let Some(body) = comment_line_body(line).and_then(|body| body.trim_start().strip_prefix(PREFIX)) else {
+                continue;
+            };
+            let line_number = index + 1;
+            let body = body.trim_start();
+            let name_len = body.find(|c: char| !is_name_char(c)).unwrap_or(body.len());
+            let rest = body[name_len..].trim_start();
+
+            if &body[..name_len] == "ignore-file" {
+                directives.ignore_file.get_or_insert(line_number);
+            } else if rest.starts_with('=') {
+                match parse_settings(body, line_number) {
+                    Ok(settings) => directives.settings.extend(settings),
+                    Err(e) => directives.warnings.push(e.to_string()),
+                }
+            }
+        }
+        directives
+    }
+
+    /// Layer the settings over `config`, which was resolved for the file at
+    /// `path`, recording each directive's line as the source.
+    pub fn apply_to(&self, config: &mut ResolvedConfig, path: &Path) {
+        let source = |line| SettingSource::Directive {
+            file: path.to_path_buf(),
+            line,
+        };
+        if let Some(line) = self.ignore_file {
+            config.ignore_file = true;
+            config.sources.ignore_file = source(line);
+        }
+        for &(name, value, line) in &self.settings {
+            let (setting, setting_source) = match name {
+                "remove-todos" => (&mut config.remove_todos, &mut config.sources.remove_todos),
+                "remove-fixme" => (&mut config.remove_fixme, &mut config.sources.remove_fixme),
+                "remove-docs" => (&mut config.remove_docs, &mut config.sources.remove_docs),
+                "use-default-ignores" => (&mut config.use_default_ignores, &mut config.sources.use_default_ignores),
+                "only-commented-code" => (&mut config.only_commented_code, &mut config.sources.only_commented_code),
+                _ => continue,
+            };
+            *setting = value;
+            *setting_source = source(line);
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// The `name=value` items of a settings directive, up to the first item that
+/// is not one.
+fn parse_settings(body: &str, line: usize) -> Result<Vec<(&'static str, bool, usize)>> {
+    body.split(',')
+        .map_while(|item| {
+            let (name, value) = item.split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty() && name.chars().all(is_name_char)).then_some((name, value))
+        })
+        .map(|(name, value)| parse_assignment(name, value, line))
+        .collect()
+}
+
+/// One `name=value` item of a settings directive. Anything after the value,
+/// such as a block comment's closing `*/`, is ignored.
+fn parse_assignment(name: &str, value: &str, line: usize) -> Result<(&'static str, bool, usize)> {
+    let name = name.replace('_', "-");
+    let Some(setting) = SETTINGS.iter().find(|setting| **setting == name) else {
+        bail!(
+            "line {line}: unknown setting `{name}`; expected one of {}",
+            SETTINGS.join(", ")
+        );
+    };
+    let value = value.split_whitespace().next().unwrap_or_default();
+    match value {
+        "true" => Ok((setting, true, line)),
+        "false" => Ok((setting, false, line)),
+        _ => bail!("line {line}: `{name}` must be true or false, found `{value}`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_settings_in_any_comment_syntax() {
+        let directives =
+            FileDirectives::parse("#!/bin/sh\n/* uncomment: remove-docs=true, remove_todos = false */\nx\n");
+        assert_eq!(directives.ignore_file, None);
+        assert_eq!(
+            directives.settings,
+            [("remove-docs", true, 2), ("remove-todos", false, 2)]
+        );
+
+        let directives = FileDirectives::parse("<!-- uncomment: ignore-file -->\n");
+        assert_eq!(directives.ignore_file, Some(1));
+
+        let directives = FileDirectives::parse("# uncomment: remove-docs=true, since the docs live elsewhere\n");
+        assert_eq!(directives.settings, [("remove-docs", true, 1)]);
+        assert!(directives.warnings.is_empty());
+    }
+
+    #[test]
+    fn ignores_region_directives_prose_and_late_lines() {
+        let content = format!(
+            "// uncomment:off\n// uncomment: a comment stripper\n{}// uncomment: ignore-file\n",
+            "x\n".repeat(DIRECTIVE_LINES)
+        );
+        assert_eq!(FileDirectives::parse(&content), FileDirectives::default());
+    }
+
+    #[test]
+    fn ignores_mentions_outside_comments_and_mid_comment() {
+        let content =
+            "MSG = \"uncomment: keep=yes\"\nurl = 'uncomment: ignore-file'\n# see https://x/uncomment: ignore-file\n";
+        assert_eq!(FileDirectives::parse(content), FileDirectives::default());
+    }
+
+    #[test]
+    fn skips_unknown_settings_and_values_with_a_warning() {
+        let directives = FileDirectives::parse("# uncomment: remove-everything=true, remove-docs=true\n");
+        assert!(directives.settings.is_empty());
+        assert!(
+            directives.warnings[0].contains("unknown setting `remove-everything`"),
+            "{:?}",
+            directives.warnings
+        );
+
+        let directives = FileDirectives::parse("\n# uncomment: remove-docs=yes\n# uncomment: remove-todos=true\n");
+        assert_eq!(
+            directives.warnings,
+            ["line 2: `remove-docs` must be true or false, found `yes`"]
+        );
+        assert_eq!(directives.settings, [("remove-todos", true, 3)]);
+    }
+}
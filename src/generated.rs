@@ -0,0 +1,161 @@
+//! Generated-file detection, so codegen output is skipped rather than
+//! rewritten and then reverted by the next codegen run.
+//!
+//! A file counts as generated when its name has a common codegen suffix, a
+//! comment in its first [`HEADER_LINES`] lines carries a marker such as
+//! `@generated` or `DO NOT EDIT`, or `.gitattributes` marks it
+//! `linguist-generated` (see [`git::generated_attributes`](crate::git::generated_attributes)).
+//! `--include-generated` processes such files anyway.
+
+use crate::commented_code::comment_line_body;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+/// How many leading lines are searched for a generated-file marker.
+pub const HEADER_LINES: usize = 10;
+
+/// Lowercased header phrases codegen tools write into their output.
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "code generated",
+    "auto-generated",
+    "autogenerated",
+    "generated by",
+    "do not edit",
+    "don't edit",
+    "do not modify",
+    "don't modify",
+];
+
+/// File name endings of common codegen output.
+const GENERATED_SUFFIXES: &[&str] = &[
+    "_pb2.py",
+    "_pb2.pyi",
+    "_pb2_grpc.py",
+    ".pb.go",
+    ".pb.gw.go",
+    ".pb.cc",
+    ".pb.h",
+    ".pb.swift",
+    ".pb.dart",
+    ".pbgrpc.dart",
+    ".g.dart",
+    ".freezed.dart",
+    ".gen.go",
+    "_gen.go",
+    "_generated.go",
+    ".generated.ts",
+    ".generated.js",
+    ".g.cs",
+    ".designer.cs",
+];
+
+/// Why a file counts as generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneratedReason {
+    /// The marker found in its header.
+    Header(&'static str),
+    /// The codegen suffix its name ends with.
+    Suffix(&'static str),
+    /// `.gitattributes` sets `linguist-generated` for it.
+    GitAttributes,
+}
+
+impl fmt::Display for GeneratedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratedReason::Header(marker) => write!(f, "header says {marker:?}"),
+            GeneratedReason::Suffix(suffix) => write!(f, "name ends with {suffix}"),
+            GeneratedReason::GitAttributes => write!(f, "linguist-generated in .gitattributes"),
+        }
+    }
+}
+
+/// Why `path` with the given start of its content counts as generated, if it
+/// does; `.gitattributes` is not consulted.
+pub fn detect(path: &Path, head: &str) -> Option<GeneratedReason> {
+    generated_suffix(path)
+        .map(GeneratedReason::Suffix)
+        .or_else(|| generated_marker(head).map(GeneratedReason::Header))
+}
+
+/// [`detect`] for a file on disk, reading only as much as the header needs.
+pub fn detect_file(path: &Path) -> Option<GeneratedReason> {
+    let mut head = Vec::new();
+    // A file that cannot be read is reported when it is processed.
+    if generated_suffix(path).is_none()
+        && let Ok(file) = std::fs::File::open(path)
+    {
+        file.take(8 * 1024).read_to_end(&mut head).ok();
+    }
+    detect(path, &String::from_utf8_lossy(&head))
+}
+
+fn generated_suffix(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    GENERATED_SUFFIXES
+        .iter()
+        .find(|suffix| name.ends_with(*suffix))
+        .copied()
+}
+
+/// The first marker on a comment line among the first [`HEADER_LINES`] lines.
+/// Only lines starting like a comment count, so a string such as
+/// `"Report generated by ..."` in code does not.
+fn generated_marker(head: &str) -> Option<&'static str> {
+    head.lines()
+        .take(HEADER_LINES)
+        .filter(|line| looks_like_comment(line))
+        .find_map(|line| {
+            let line = line.to_lowercase();
+            GENERATED_MARKERS.iter().find(|marker| line.contains(*marker)).copied()
+        })
+}
+
+fn looks_like_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    comment_line_body(line).is_some() || ["\"\"\"", "'''"].iter().any(|leader| line.starts_with(leader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_codegen_headers_in_comments_only() {
+        let go = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n";
+        assert_eq!(
+            detect(Path::new("api.go"), go),
+            Some(GeneratedReason::Header("code generated"))
+        );
+        assert_eq!(
+            detect(Path::new("a.py"), "#!/usr/bin/env python\n# @generated by tool\n"),
+            Some(GeneratedReason::Header("@generated"))
+        );
+        assert_eq!(
+            detect(Path::new("a.js"), "/**\n * Auto-generated file\n */\n"),
+            Some(GeneratedReason::Header("auto-generated"))
+        );
+
+        assert_eq!(
+            detect(Path::new("a.py"), "TITLE = \"Report generated by hand\"\n"),
+            None
+        );
+        let late = format!("{}// DO NOT EDIT\n", "x = 1\n".repeat(HEADER_LINES));
+        assert_eq!(detect(Path::new("a.rs"), &late), None);
+    }
+
+    #[test]
+    fn detects_codegen_suffixes() {
+        assert_eq!(
+            detect(Path::new("proto/api_pb2.py"), ""),
+            Some(GeneratedReason::Suffix("_pb2.py"))
+        );
+        assert_eq!(
+            detect(Path::new("Form1.Designer.cs"), ""),
+            Some(GeneratedReason::Suffix(".designer.cs"))
+        );
+        assert_eq!(detect(Path::new("src/main.go"), ""), None);
+    }
+}
@@ -0,0 +1,338 @@
+//! Thin wrappers over the `git` CLI for `--staged` and `--since` runs.
+//!
+//! Shelling out keeps uncomment free of a libgit2 dependency and guarantees the
+//! user's own git (hooks config, attributes, index format) is what reads and
+//! writes the index.
+
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A regular file staged for commit.
+#[derive(Debug, Clone)]
+pub struct StagedFile {
+    /// Path relative to the repository root.
+    pub path: PathBuf,
+    /// Object id of the staged blob.
+    pub blob: String,
+}
+
+/// Run `git` in `dir`, feeding `stdin` if given, and return its stdout.
+fn run_git(dir: &Path, args: &[&str], stdin: Option<&[u8]>) -> Result<Vec<u8>> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git; is it installed and on PATH?")?;
+
+    // Feed stdin from another thread so git can fill its stdout pipe while it
+    // is still reading; writing it all first deadlocks on large inputs.
+    let output = std::thread::scope(|scope| {
+        let writer = stdin
+            .zip(child.stdin.take())
+            .map(|(input, mut pipe)| scope.spawn(move || pipe.write_all(input).context("Failed to write to git")));
+        let output = child.wait_with_output().context("Failed to wait for git")?;
+        if let Some(writer) = writer {
+            writer.join().expect("git stdin writer panicked")?;
+        }
+        anyhow::Ok(output)
+    })?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Top-level directory of the repository containing `dir`.
+pub fn repo_root(dir: &Path) -> Result<PathBuf> {
+    let stdout = run_git(dir, &["rev-parse", "--show-toplevel"], None).context("Not inside a git repository")?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&stdout).trim_end()))
+}
+
+/// How many paths one `git check-attr` call gets on its command line.
+const CHECK_ATTR_BATCH: usize = 500;
+
+/// Which of `paths` (relative to `dir` or absolute) `.gitattributes` marks
+/// `linguist-generated`, in the order given. Paths go in batches as arguments
+/// rather than on stdin, so git exiting early cannot leave us writing to a
+/// closed pipe.
+pub fn generated_attributes(dir: &Path, paths: &[PathBuf]) -> Result<Vec<bool>> {
+    let mut generated = Vec::with_capacity(paths.len());
+    for batch in paths.chunks(CHECK_ATTR_BATCH) {
+        let batch: Vec<String> = batch.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+        let mut args = vec!["check-attr", "-z", "linguist-generated", "--"];
+        args.extend(batch.iter().map(String::as_str));
+        let stdout = run_git(dir, &args, None)?;
+        // Records are `<path> NUL <attribute> NUL <value> NUL`, one per path.
+        let values: Vec<bool> = stdout
+            .split(|&byte| byte == 0)
+            .skip(2)
+            .step_by(3)
+            .take(batch.len())
+            .map(|value| value == b"set" || value == b"true")
+            .collect();
+        if values.len() != batch.len() {
+            bail!("git check-attr returned {} of {} paths", values.len(), batch.len());
+        }
+        generated.extend(values);
+    }
+    Ok(generated)
+}
+
+/// Regular files added, copied or modified in the index (a rename counts as an
+/// addition), optionally limited to `pathspecs` (relative to `dir`, as git
+/// interprets them).
+pub fn staged_files(dir: &Path, pathspecs: &[String]) -> Result<Vec<StagedFile>> {
+    let mut args = vec![
+        "diff",
+        "--cached",
+        "--raw",
+        "-z",
+        "--no-abbrev",
+        "--no-renames",
+        "--diff-filter=ACM",
+        "--",
+    ];
+    args.extend(pathspecs.iter().map(String::as_str));
+    let stdout = run_git(dir, &args, None)?;
+    Ok(parse_raw_diff(&stdout))
+}
+
+/// Parse `git diff --raw -z` records: `:<old mode> <new mode> <old id> <new id> <status>\0<path>\0`.
+fn parse_raw_diff(stdout: &[u8]) -> Vec<StagedFile> {
+    let mut files = Vec::new();
+    let mut fields = stdout.split(|&byte| byte == 0).filter(|field| !field.is_empty());
+
+    while let (Some(header), Some(path)) = (fields.next(), fields.next()) {
+        let header = String::from_utf8_lossy(header);
+        let parts: Vec<&str> = header.trim_start_matches(':').split(' ').collect();
+        let [_, new_mode, _, new_id, _status] = parts.as_slice() else {
+            continue;
+        };
+        // Skip symlinks (120000) and submodules (160000); only blobs hold source.
+        if !matches!(*new_mode, "100644" | "100755") {
+            continue;
+        }
+        files.push(StagedFile {
+            path: PathBuf::from(String::from_utf8_lossy(path).into_owned()),
+            blob: (*new_id).to_string(),
+        });
+    }
+    files
+}
+
+/// Content of blob `id`.
+pub fn read_blob(dir: &Path, id: &str) -> Result<String> {
+    let stdout = run_git(dir, &["cat-file", "blob", id], None)?;
+    String::from_utf8(stdout).with_context(|| format!("Staged blob {id} is not valid UTF-8"))
+}
+
+/// Replace the staged content of `path` (relative to `root`), keeping its mode.
+pub fn stage_content(root: &Path, path: &Path, content: &str) -> Result<()> {
+    let path_str = path.to_string_lossy();
+    let listing = run_git(root, &["ls-files", "--stage", "-z", "--", &path_str], None)?;
+    let listing = String::from_utf8_lossy(&listing);
+    let mode = listing
+        .split(' ')
+        .next()
+        .filter(|mode| !mode.is_empty())
+        .with_context(|| format!("{} is not in the index", path.display()))?;
+
+    let blob = run_git(
+        root,
+        &["hash-object", "-w", "--no-filters", "--stdin"],
+        Some(content.as_bytes()),
+    )?;
+    let blob = String::from_utf8_lossy(&blob);
+    let cacheinfo = format!("{mode},{},{path_str}", blob.trim());
+    run_git(root, &["update-index", "--cacheinfo", &cacheinfo], None)?;
+    Ok(())
+}
+
+/// Apply `patch` (with paths relative to `root`) to the working tree only.
+pub fn apply_to_worktree(root: &Path, patch: &str) -> Result<()> {
+    run_git(root, &["apply", "-"], Some(patch.as_bytes())).map(|_| ())
+}
+
+/// Lines added or changed since `rev`, per file relative to `root`, as 1-based
+/// inclusive ranges. With `cached` the index is compared against `rev`;
+/// otherwise the working tree is, and untracked files count as wholly changed.
+pub fn changed_lines(root: &Path, rev: &str, cached: bool) -> Result<HashMap<PathBuf, Vec<RangeInclusive<usize>>>> {
+    if rev.starts_with('-') {
+        bail!("Invalid revision: {rev}");
+    }
+    let commit = format!("{rev}^{{commit}}");
+    run_git(root, &["rev-parse", "--verify", "--quiet", &commit], None)
+        .with_context(|| format!("Unknown revision: {rev}"))?;
+
+    let mut args = vec![
+        "diff",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        "--no-textconv",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ];
+    if cached {
+        args.push("--cached");
+    }
+    args.extend([rev, "--"]);
+    let stdout = run_git(root, &args, None)?;
+    let mut changes = parse_unified_diff(&String::from_utf8_lossy(&stdout));
+
+    if !cached {
+        let untracked = run_git(root, &["ls-files", "--others", "--exclude-standard", "-z"], None)?;
+        for path in untracked.split(|&byte| byte == 0).filter(|path| !path.is_empty()) {
+            let path = PathBuf::from(String::from_utf8_lossy(path).into_owned());
+            changes.insert(path, vec![1..=usize::MAX]);
+        }
+    }
+    Ok(changes)
+}
+
+/// New-side line ranges of every hunk in a `--unified=0` diff, keyed by the
+/// post-image path. Files that only lost lines are left out.
+fn parse_unified_diff(diff: &str) -> HashMap<PathBuf, Vec<RangeInclusive<usize>>> {
+    let mut changes: HashMap<PathBuf, Vec<RangeInclusive<usize>>> = HashMap::new();
+    let mut current: Option<PathBuf> = None;
+    // Body lines left in the current hunk; an added line may itself start with "+++ ".
+    let mut remaining = 0usize;
+
+    for line in diff.lines() {
+        if remaining > 0 {
+            if !line.starts_with('\\') {
+                remaining -= 1;
+            }
+            continue;
+        }
+
+        if line.starts_with("diff ") {
+            current = None;
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = unquote(path);
+            current = path.strip_prefix("b/").map(PathBuf::from);
+        } else if let Some(header) = line.strip_prefix("@@ ")
+            && let Some((old_len, new_start, new_len)) = parse_hunk_header(header)
+        {
+            remaining = old_len + new_len;
+            if let Some(path) = &current
+                && new_len > 0
+            {
+                changes
+                    .entry(path.clone())
+                    .or_default()
+                    .push(new_start..=new_start + new_len - 1);
+            }
+        }
+    }
+    changes
+}
+
+/// `-a[,b] +c[,d] @@` → (b, c, d), with omitted lengths defaulting to 1.
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = header.split(' ');
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+
+    let span = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (_, old_len) = span(old)?;
+    let (new_start, new_len) = span(new)?;
+    Some((old_len, new_start, new_len))
+}
+
+/// Undo git's C-style quoting of unusual paths (`"a\tb"`, octal `\303\251`).
+fn unquote(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = u32::from(digit - b'0');
+                for _ in 0..2 {
+                    if let Some(next @ b'0'..=b'7') = chars.clone().next() {
+                        value = value * 8 + u32::from(next - b'0');
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_raw_records_and_skips_non_blobs() {
+        let raw = b":000000 100644 0000000 1111111 A\0src/new.rs\0\
+                    :100644 100755 2222222 3333333 M\0run.sh\0\
+                    :000000 120000 0000000 4444444 A\0link.rs\0\
+                    :000000 160000 0000000 5555555 A\0vendor/sub\0";
+        let files = parse_raw_diff(raw);
+
+        let paths: Vec<_> = files.iter().map(|file| file.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["src/new.rs", "run.sh"]);
+        assert_eq!(files[1].blob, "3333333");
+    }
+
+    #[test]
+    fn collects_new_side_hunk_ranges_per_file() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n\
+                    --- a/src/a.rs\n\
+                    +++ b/src/a.rs\n\
+                    @@ -3 +3,2 @@ fn main() {\n\
+                    -old\n\
+                    +++ added line that looks like a header\n\
+                    +new\n\
+                    @@ -10,2 +11,0 @@\n\
+                    -gone\n\
+                    -gone\n\
+                    @@ -20,0 +21 @@\n\
+                    +tail\n\
+                    diff --git a/old.rs b/old.rs\n\
+                    --- a/old.rs\n\
+                    +++ /dev/null\n\
+                    @@ -1 +0,0 @@\n\
+                    -x\n\
+                    diff --git \"a/caf\\303\\251.rs\" \"b/caf\\303\\251.rs\"\n\
+                    --- \"a/caf\\303\\251.rs\"\n\
+                    +++ \"b/caf\\303\\251.rs\"\n\
+                    @@ -0,0 +1,4 @@\n";
+        let changes = parse_unified_diff(diff);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[Path::new("src/a.rs")], [3..=4, 21..=21]);
+        assert_eq!(changes[Path::new("café.rs")], [1..=4]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Syntax-tree dumps for authoring `[languages]` configs (`uncomment inspect`).
+//!
+//! Shows the node kinds a grammar produces and which of them the visitor
+//! treats as comments, so `comment_nodes` and `doc_comment_nodes` can be chosen
+//! without the tree-sitter CLI.
+
+/// How the visitor treats a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeComment {
+    Comment,
+    Doc,
+}
+
+/// One syntax-tree node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectedNode {
+    /// Nesting depth; the root is 0.
+    pub depth: usize,
+    pub kind: &'static str,
+    /// Named nodes are the grammar's rules; anonymous ones are literal tokens.
+    pub named: bool,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 0-based row and byte column.
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    /// `Some` when the visitor collects this node as a comment.
+    pub comment: Option<NodeComment>,
+}
+
+/// A parsed file's tree with the language settings it was inspected under.
+#[derive(Debug, Clone)]
+pub struct Inspection {
+    pub language: String,
+    pub tslp_name: String,
+    pub comment_types: Vec<String>,
+    pub doc_comment_types: Vec<String>,
+    /// Every node in pre-order.
+    pub nodes: Vec<InspectedNode>,
+}
+
+impl Inspection {
+    /// Nodes worth printing: named ones, plus anonymous ones only when `all` is
+    /// set or the visitor treats them as comments.
+    pub fn visible_nodes(&self, all: bool) -> impl Iterator<Item = &InspectedNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| all || node.named || node.comment.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ConfigManager};
+    use crate::processor::Processor;
+
+    #[test]
+    fn marks_comment_and_doc_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_manager = ConfigManager::from_single_config(dir.path(), Config::default()).unwrap();
+        let mut processor = Processor::new_with_config(&config_manager);
+        let source = "\"\"\"Module docs.\"\"\"\n# note\nx = \"text\"\n";
+        let inspection = processor
+            .inspect(&dir.path().join("a.py"), source, &config_manager)
+            .unwrap();
+
+        let marked: Vec<(&str, usize, Option<NodeComment>)> = inspection
+            .nodes
+            .iter()
+            .filter(|node| node.kind == "comment" || node.kind == "string")
+            .map(|node| (node.kind, node.start.0, node.comment))
+            .collect();
+        assert_eq!(
+            marked,
+            [
+                ("string", 0, Some(NodeComment::Doc)),
+                ("comment", 1, Some(NodeComment::Comment)),
+                ("string", 2, None),
+            ]
+        );
+
+        let root = &inspection.nodes[0];
+        assert_eq!((root.kind, root.depth, root.start_byte), ("module", 0, 0));
+        assert!(inspection.visible_nodes(false).all(|node| node.named));
+        assert!(inspection.visible_nodes(true).count() > inspection.visible_nodes(false).count());
+    }
+}
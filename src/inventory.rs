@@ -0,0 +1,112 @@
+//! Comment inventories for auditing a tree before stripping it
+//! (`--list-comments json|csv`).
+//!
+//! Unlike a run report, which only carries what was removed, an inventory has
+//! every comment the visitor found and whether the run would keep it.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One comment of one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommentRecord {
+    pub path: PathBuf,
+    pub language: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 0-based first line of the comment.
+    pub start_row: usize,
+    /// 0-based last line of the comment.
+    pub end_row: usize,
+    pub node_type: &'static str,
+    pub is_documentation: bool,
+    /// Whether a run with the same settings would keep the comment.
+    pub preserved: bool,
+    pub text: String,
+}
+
+const CSV_HEADER: &str =
+    "path,language,start_byte,end_byte,start_row,end_row,node_type,is_documentation,preserved,text";
+
+/// Write the records as one JSON array.
+pub fn write_json(out: &mut impl Write, records: &[CommentRecord]) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *out, records).context("Failed to serialize comment inventory")?;
+    writeln!(out).context("Failed to write comment inventory")?;
+    Ok(())
+}
+
+/// Write the records as RFC 4180 CSV with a header row. Text keeps its line
+/// breaks inside a quoted field.
+pub fn write_csv(out: &mut impl Write, records: &[CommentRecord]) -> Result<()> {
+    writeln!(out, "{CSV_HEADER}").context("Failed to write comment inventory")?;
+    for record in records {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&record.path.to_string_lossy()),
+            csv_field(&record.language),
+            record.start_byte,
+            record.end_byte,
+            record.start_row,
+            record.end_row,
+            csv_field(record.node_type),
+            record.is_documentation,
+            record.preserved,
+            csv_field(&record.text)
+        )
+        .context("Failed to write comment inventory")?;
+    }
+    Ok(())
+}
+
+/// `value`, quoted with inner quotes doubled when it holds a comma, quote or line break.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(text: &str) -> CommentRecord {
+        CommentRecord {
+            path: PathBuf::from("src/a.rs"),
+            language: "rust".to_string(),
+            start_byte: 0,
+            end_byte: text.len(),
+            start_row: 0,
+            end_row: 1,
+            node_type: "block_comment",
+            is_documentation: false,
+            preserved: true,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &[record("/* a, \"b\"\n c */")]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{CSV_HEADER}\nsrc/a.rs,rust,0,15,0,1,block_comment,false,true,\"/* a, \"\"b\"\"\n c */\"\n")
+        );
+    }
+
+    #[test]
+    fn json_is_an_array_of_records() {
+        let mut out = Vec::new();
+        write_json(&mut out, &[record("// x")]).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value[0]["node_type"], "block_comment");
+        assert_eq!(value[0]["text"], "// x");
+        assert_eq!(value[0]["preserved"], true);
+    }
+}
@@ -0,0 +1,365 @@
+//! Undo journal for real (non-dry-run) runs.
+//!
+//! Before [`OutputWriter::write_file`](crate::processor::OutputWriter::write_file)
+//! overwrites a file, its original content is copied into
+//! `.uncomment/history/<run-id>/` under the directory the run started in, and a
+//! `[[files]]` entry is appended to that run's `manifest.toml`. Each entry also
+//! records a fingerprint of the content uncomment wrote, so `uncomment undo`
+//! can refuse to clobber a file that was edited after the run.
+
+use crate::atomic_write::WriteOptions;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory (relative to the run's working directory) that holds journals.
+pub const JOURNAL_DIR: &str = ".uncomment";
+const HISTORY_DIR: &str = "history";
+const MANIFEST_FILE: &str = "manifest.toml";
+
+/// Whether `path` lies inside a journal directory and must never be processed.
+pub fn is_journal_path(path: &Path) -> bool {
+    path.components().any(|component| component.as_os_str() == JOURNAL_DIR)
+}
+
+/// `<base>/.uncomment/history`, where every run's journal lives.
+pub fn history_dir(base: &Path) -> PathBuf {
+    base.join(JOURNAL_DIR).join(HISTORY_DIR)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Absolute path of the rewritten file.
+    path: PathBuf,
+    /// File name of the original content, relative to the run directory.
+    blob: String,
+    /// [`fingerprint`] of the content the run wrote.
+    fingerprint: String,
+}
+
+/// Records the originals of one run. Nothing touches the disk until the first
+/// file is recorded, so runs that change nothing leave no journal behind.
+pub struct Journal {
+    base: PathBuf,
+    run_id: String,
+    recorded: Mutex<usize>,
+}
+
+impl Journal {
+    /// Start a journal for a run rooted at `base` (normally the current directory).
+    pub fn new(base: &Path) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self {
+            base: base.to_path_buf(),
+            run_id: format!("{}-{}", utc_timestamp(now.as_secs()), std::process::id()),
+            recorded: Mutex::new(0),
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Whether at least one file has been journaled in this run.
+    pub fn has_entries(&self) -> bool {
+        self.recorded.lock().map(|count| *count > 0).unwrap_or(false)
+    }
+
+    fn run_dir(&self) -> PathBuf {
+        history_dir(&self.base).join(&self.run_id)
+    }
+
+    /// Save `original` for `path` before it is overwritten with `processed`.
+    pub fn record(&self, path: &Path, original: &str, processed: &str) -> Result<()> {
+        let mut recorded = self
+            .recorded
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Journal lock poisoned"))?;
+        let run_dir = self.run_dir();
+
+        if *recorded == 0 {
+            std::fs::create_dir_all(&run_dir)
+                .with_context(|| format!("Failed to create journal directory: {}", run_dir.display()))?;
+            // Keep journals out of version control without touching the project's .gitignore.
+            let ignore_file = self.base.join(JOURNAL_DIR).join(".gitignore");
+            if !ignore_file.exists() {
+                std::fs::write(&ignore_file, "*\n")
+                    .with_context(|| format!("Failed to write {}", ignore_file.display()))?;
+            }
+        }
+
+        let blob = format!("{}.orig", *recorded);
+        std::fs::write(run_dir.join(&blob), original)
+            .with_context(|| format!("Failed to journal original of {}", path.display()))?;
+
+        let absolute_path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base.join(path)
+        };
+        let entry = Manifest {
+            files: vec![ManifestEntry {
+                path: absolute_path,
+                blob,
+                fingerprint: fingerprint(processed),
+            }],
+        };
+        let serialized = toml::to_string(&entry).context("Failed to serialize journal entry")?;
+        let manifest_path = run_dir.join(MANIFEST_FILE);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&manifest_path)
+            .and_then(|mut file| writeln!(file, "{serialized}"))
+            .with_context(|| format!("Failed to update journal manifest: {}", manifest_path.display()))?;
+
+        *recorded += 1;
+        Ok(())
+    }
+}
+
+/// A journaled run, as listed by `uncomment undo --list`.
+#[derive(Debug)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub files: usize,
+}
+
+/// All journaled runs under `base`, oldest first.
+pub fn list_runs(base: &Path) -> Result<Vec<RunSummary>> {
+    let history = history_dir(base);
+    if !history.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut runs = Vec::new();
+    for entry in std::fs::read_dir(&history).with_context(|| format!("Failed to read {}", history.display()))? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let run_id = entry.file_name().to_string_lossy().into_owned();
+        let files = read_manifest(&entry.path())?.files.len();
+        runs.push(RunSummary { run_id, files });
+    }
+    runs.sort_by(|a, b| a.run_id.cmp(&b.run_id));
+    Ok(runs)
+}
+
+/// What happened to one journaled file during `undo`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum UndoStatus {
+    Restored,
+    /// The file already holds its original content.
+    AlreadyRestored,
+    /// The file no longer matches what the run wrote, so it was left alone.
+    ChangedSinceRun,
+    /// The file no longer exists.
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct UndoReport {
+    pub run_id: String,
+    pub files: Vec<(PathBuf, UndoStatus)>,
+}
+
+impl UndoReport {
+    /// Whether every file is back to its original content.
+    pub fn is_complete(&self) -> bool {
+        self.files
+            .iter()
+            .all(|(_, status)| matches!(status, UndoStatus::Restored | UndoStatus::AlreadyRestored))
+    }
+}
+
+/// Restore the files of `run_id` (or of the most recent run). The journal is
+/// deleted once every file is back to its original content; otherwise it is
+/// kept so the remaining files can be resolved and the undo retried.
+pub fn undo(base: &Path, run_id: Option<&str>) -> Result<UndoReport> {
+    let run_id = match run_id {
+        Some(run_id) => run_id.to_string(),
+        None => match list_runs(base)?.pop() {
+            Some(latest) => latest.run_id,
+            None => bail!("No journaled runs found in {}", history_dir(base).display()),
+        },
+    };
+
+    // The run directory is deleted after a complete undo, so never let an id escape the history.
+    if run_id.is_empty() || run_id.starts_with('.') || run_id.contains(['/', '\\']) {
+        bail!("Invalid run id '{run_id}'");
+    }
+    let run_dir = history_dir(base).join(&run_id);
+    if !run_dir.is_dir() {
+        bail!("No journaled run named '{run_id}' in {}", history_dir(base).display());
+    }
+
+    let manifest = read_manifest(&run_dir)?;
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for entry in manifest.files {
+        let original = std::fs::read_to_string(run_dir.join(&entry.blob))
+            .with_context(|| format!("Journal is missing the original of {}", entry.path.display()))?;
+
+        let status = match std::fs::read_to_string(&entry.path) {
+            Err(_) => UndoStatus::Missing,
+            Ok(current) if current == original => UndoStatus::AlreadyRestored,
+            Ok(current) if fingerprint(&current) != entry.fingerprint => UndoStatus::ChangedSinceRun,
+            Ok(_) => {
+                crate::atomic_write::write_atomic(&entry.path, original.as_bytes(), WriteOptions::default())
+                    .with_context(|| format!("Failed to restore {}", entry.path.display()))?;
+                UndoStatus::Restored
+            }
+        };
+        files.push((entry.path, status));
+    }
+
+    let report = UndoReport { run_id, files };
+    if report.is_complete() {
+        std::fs::remove_dir_all(&run_dir)
+            .with_context(|| format!("Failed to remove journal: {}", run_dir.display()))?;
+    }
+    Ok(report)
+}
+
+fn read_manifest(run_dir: &Path) -> Result<Manifest> {
+    let manifest_path = run_dir.join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        return Ok(Manifest::default());
+    }
+    let content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read journal manifest: {}", manifest_path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Corrupt journal manifest: {}", manifest_path.display()))
+}
+
+/// 64-bit FNV-1a of `content`, hex-encoded. Stable across builds and platforms,
+/// unlike `std`'s `DefaultHasher`; used only to detect edits, not for security.
+fn fingerprint(content: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = content
+        .bytes()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME));
+    format!("{hash:016x}")
+}
+
+/// `YYYYMMDDTHHMMSSZ` for a Unix timestamp, so run ids sort chronologically.
+fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant), valid for the whole proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn journaled_run(dir: &Path, file: &Path, original: &str, processed: &str) -> String {
+        std::fs::write(file, original).unwrap();
+        let journal = Journal::new(dir);
+        journal.record(file, original, processed).unwrap();
+        std::fs::write(file, processed).unwrap();
+        journal.run_id().to_string()
+    }
+
+    #[test]
+    fn utc_timestamp_formats_known_instants() {
+        assert_eq!(utc_timestamp(0), "19700101T000000Z");
+        assert_eq!(utc_timestamp(951_782_400), "20000229T000000Z");
+        assert_eq!(utc_timestamp(1_760_616_245), "20251016T120405Z");
+    }
+
+    #[test]
+    fn journal_is_lazy_and_ignored_by_git() {
+        let dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            String::from_utf8(output.stdout).unwrap()
+        };
+        let file = dir.path().join("a.rs");
+        std::fs::write(&file, "// x\nfn f() {}\n").unwrap();
+        git(&["init", "-q"]);
+        git(&["add", "a.rs"]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        // Like a dry run, a journal that never records writes nothing.
+        let _ = Journal::new(dir.path());
+        assert!(!dir.path().join(JOURNAL_DIR).exists());
+
+        let run_id = journaled_run(dir.path(), &file, "// x\nfn f() {}\n", "fn f() {}\n");
+        assert!(history_dir(dir.path()).join(run_id).join(MANIFEST_FILE).is_file());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".uncomment/.gitignore")).unwrap(),
+            "*\n"
+        );
+        // Only the rewritten file shows up; the journal itself stays invisible.
+        assert_eq!(git(&["status", "--porcelain", "--untracked-files=all"]), " M a.rs\n");
+    }
+
+    #[test]
+    fn undo_restores_and_removes_the_journal() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.rs");
+        let run_id = journaled_run(dir.path(), &file, "// x\nfn f() {}\n", "fn f() {}\n");
+
+        let report = undo(dir.path(), None).unwrap();
+
+        assert_eq!(report.run_id, run_id);
+        assert_eq!(report.files, vec![(file.clone(), UndoStatus::Restored)]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "// x\nfn f() {}\n");
+        assert!(list_runs(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn undo_refuses_files_changed_since_the_run() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.rs");
+        let run_id = journaled_run(dir.path(), &file, "// x\nfn f() {}\n", "fn f() {}\n");
+        std::fs::write(&file, "fn f() { edited() }\n").unwrap();
+
+        let report = undo(dir.path(), Some(&run_id)).unwrap();
+
+        assert_eq!(report.files, vec![(file.clone(), UndoStatus::ChangedSinceRun)]);
+        assert!(!report.is_complete());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn f() { edited() }\n");
+        assert_eq!(list_runs(dir.path()).unwrap().len(), 1, "journal kept for retry");
+    }
+
+    #[test]
+    fn is_journal_path_matches_any_component() {
+        assert!(is_journal_path(Path::new("./.uncomment/history/x/manifest.toml")));
+        assert!(!is_journal_path(Path::new("src/uncomment.rs")));
+    }
+}
@@ -4,6 +4,11 @@ pub trait LanguageHandler {
     fn is_documentation_comment(&self, node: &Node, parent: Option<Node>, source: &str) -> Option<bool>;
 
     fn should_preserve_comment(&self, node: &Node, parent: Option<Node>, source: &str) -> Option<bool>;
+
+    /// Name of a language-specific handler, or `None` for the default one.
+    fn name(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 pub struct DefaultHandler;
@@ -21,6 +26,10 @@ impl LanguageHandler for DefaultHandler {
 pub struct PythonHandler;
 
 impl LanguageHandler for PythonHandler {
+    fn name(&self) -> Option<&'static str> {
+        Some("python")
+    }
+
     fn is_documentation_comment(&self, node: &Node, parent: Option<Node>, _source: &str) -> Option<bool> {
         if node.kind() != "string" {
             return None;
@@ -86,6 +95,10 @@ impl PythonHandler {
 pub struct GoHandler;
 
 impl LanguageHandler for GoHandler {
+    fn name(&self) -> Option<&'static str> {
+        Some("go")
+    }
+
     fn is_documentation_comment(&self, node: &Node, parent: Option<Node>, _source: &str) -> Option<bool> {
         if node.kind() != "comment" {
             return None;
@@ -207,6 +220,10 @@ pub fn get_handler(language_name: &str) -> Box<dyn LanguageHandler> {
 pub struct CFamilyHandler;
 
 impl LanguageHandler for CFamilyHandler {
+    fn name(&self) -> Option<&'static str> {
+        Some("c-family")
+    }
+
     fn is_documentation_comment(&self, _node: &Node, _parent: Option<Node>, _source: &str) -> Option<bool> {
         None
     }
@@ -244,6 +261,10 @@ impl CFamilyHandler {
 pub struct RubyHandler;
 
 impl LanguageHandler for RubyHandler {
+    fn name(&self) -> Option<&'static str> {
+        Some("ruby")
+    }
+
     fn is_documentation_comment(&self, node: &Node, parent: Option<Node>, source: &str) -> Option<bool> {
         if node.kind() != "comment" {
             return None;
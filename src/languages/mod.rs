@@ -1,6 +1,7 @@
 pub mod config;
 pub mod handlers;
 pub mod registry;
+pub mod sniff;
 
 pub use config::LanguageConfig;
 pub use handlers::{LanguageHandler, get_handler};
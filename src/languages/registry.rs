@@ -1,11 +1,32 @@
 use crate::languages::config::LanguageConfig;
-use ahash::AHashMap;
+use crate::languages::handlers::get_handler;
+use crate::languages::sniff;
+use ahash::{AHashMap, AHashSet};
+use serde::Serialize;
 use std::path::Path;
 use std::sync::Arc;
 
 pub struct LanguageRegistry {
     languages: AHashMap<String, Arc<LanguageConfig>>,
     extension_map: AHashMap<String, String>,
+    /// Languages defined or redefined by `[languages.*]` config entries.
+    configured: AHashSet<String>,
+}
+
+/// A registered language as `uncomment languages` reports it.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageSummary {
+    pub name: String,
+    /// `"built-in"`, or `"config"` for languages defined or redefined in config.
+    pub source: &'static str,
+    pub extensions: Vec<String>,
+    pub comment_types: Vec<String>,
+    pub doc_comment_types: Vec<String>,
+    pub tslp_name: String,
+    /// The language-specific [`LanguageHandler`](crate::languages::LanguageHandler), if any.
+    pub handler: Option<&'static str>,
+    /// Whether the grammar is compiled in or already downloaded.
+    pub grammar_available: bool,
 }
 
 impl LanguageRegistry {
@@ -13,6 +34,7 @@ impl LanguageRegistry {
         let mut registry = Self {
             languages: AHashMap::new(),
             extension_map: AHashMap::new(),
+            configured: AHashSet::new(),
         };
 
         registry.register_default_languages();
@@ -99,6 +121,24 @@ impl LanguageRegistry {
         self.languages.get(&name.to_lowercase()).cloned()
     }
 
+    /// Look a language up by name, falling back to treating `name` as a file
+    /// extension, so `py` finds Python just as `python` does.
+    #[must_use]
+    pub fn find_language_arc(&self, name: &str) -> Option<Arc<LanguageConfig>> {
+        self.get_language_arc(name).or_else(|| {
+            let language_name = self.extension_map.get(&name.trim_start_matches('.').to_lowercase())?;
+            self.languages.get(language_name).cloned()
+        })
+    }
+
+    /// Detect a language from a `#!` line or a Vim / Emacs modeline at the start
+    /// of `content`, for files whose name does not identify one.
+    #[must_use]
+    pub fn detect_language_from_content(&self, content: &str) -> Option<Arc<LanguageConfig>> {
+        let hint = sniff::language_hint(content)?;
+        self.find_language_arc(sniff::alias(&hint).unwrap_or(&hint))
+    }
+
     pub fn detect_language(&self, file_path: &Path) -> Option<&LanguageConfig> {
         let language_name = self.detect_language_name(file_path)?;
         self.languages.get(language_name).map(Arc::as_ref)
@@ -195,9 +235,38 @@ impl LanguageRegistry {
                 doc_comment_types: config.doc_comment_nodes.clone(),
                 tslp_name,
             };
+            self.configured.insert(name_lower);
             self.register_language(language_config);
         }
     }
+
+    /// Every registered language, sorted by name, with where it came from and
+    /// whether its grammar can be loaded without a download.
+    pub fn summaries(&self) -> Vec<LanguageSummary> {
+        let downloaded: AHashSet<String> = tree_sitter_language_pack::downloaded_languages().into_iter().collect();
+
+        let mut summaries: Vec<LanguageSummary> = self
+            .languages
+            .iter()
+            .map(|(name, config)| LanguageSummary {
+                name: name.clone(),
+                source: if self.configured.contains(name) {
+                    "config"
+                } else {
+                    "built-in"
+                },
+                extensions: config.extensions.clone(),
+                comment_types: config.comment_types.clone(),
+                doc_comment_types: config.doc_comment_types.clone(),
+                tslp_name: config.tslp_name.clone(),
+                handler: get_handler(name).name(),
+                grammar_available: tree_sitter_language_pack::has_parser(&config.tslp_name)
+                    || downloaded.contains(&config.tslp_name),
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
 }
 
 impl Default for LanguageRegistry {
@@ -356,6 +425,59 @@ mod tests {
         assert_eq!(registry.language_for_extension("cst"), Some("custom".to_string()));
     }
 
+    #[test]
+    fn test_language_detection_by_content() {
+        let registry = LanguageRegistry::new();
+        let detect = |content: &str| {
+            registry
+                .detect_language_from_content(content)
+                .map(|config| config.name.clone())
+        };
+
+        assert_eq!(detect("#!/usr/bin/env python3\n").as_deref(), Some("python"));
+        assert_eq!(detect("#!/bin/bash\n").as_deref(), Some("shell"));
+        assert_eq!(detect("#!/usr/bin/env node\n").as_deref(), Some("javascript"));
+        assert_eq!(detect("# vim: ft=yaml\nkey: 1\n").as_deref(), Some("yaml"));
+        assert_eq!(detect("/* -*- mode: c++ -*- */\n").as_deref(), Some("cpp"));
+        assert_eq!(detect("#!/usr/bin/env klingon\n"), None);
+        assert_eq!(detect("plain text\n"), None);
+    }
+
+    #[test]
+    fn test_summaries_mark_configured_languages_and_handlers() {
+        let mut registry = LanguageRegistry::new();
+        let mut configured = std::collections::HashMap::new();
+        configured.insert(
+            "python".to_string(),
+            crate::config::LanguageConfig {
+                name: "Python".to_string(),
+                extensions: vec!["py".to_string()],
+                comment_nodes: vec!["comment".to_string()],
+                doc_comment_nodes: vec![],
+                preserve_patterns: vec![],
+                remove_patterns: vec![],
+                preserve_regex: vec![],
+                remove_regex: vec![],
+                remove_todos: None,
+                remove_fixme: None,
+                remove_docs: None,
+                use_default_ignores: None,
+                only_commented_code: None,
+            },
+        );
+        registry.register_configured_languages(&configured);
+
+        let summaries = registry.summaries();
+        let python = summaries.iter().find(|summary| summary.name == "python").unwrap();
+        let rust = summaries.iter().find(|summary| summary.name == "rust").unwrap();
+
+        assert_eq!(python.source, "config");
+        assert_eq!(python.handler, Some("python"));
+        assert_eq!(rust.source, "built-in");
+        assert_eq!(rust.handler, None);
+        assert!(summaries.windows(2).all(|pair| pair[0].name <= pair[1].name));
+    }
+
     #[test]
     fn test_extensions_for_language() {
         let registry = LanguageRegistry::new();
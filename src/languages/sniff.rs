@@ -0,0 +1,153 @@
+//! Language hints embedded in a file's first lines: `#!` interpreter lines and
+//! Vim / Emacs modelines. Used when a file's name alone does not identify it.
+
+/// Bytes from the start of a file that are examined for hints.
+pub const SNIFF_BYTES: usize = 512;
+
+/// Lines from the start of a file searched for a modeline, as Vim does by default.
+const MODELINE_LINES: usize = 5;
+
+/// Interpreter and editor-mode names that differ from a registered language's
+/// name and from its extensions. Anything else is looked up as a language
+/// name, then as an extension.
+const ALIASES: &[(&str, &str)] = &[
+    ("ash", "shell"),
+    ("bb", "clojure"),
+    ("bun", "javascript"),
+    ("c++", "cpp"),
+    ("cperl", "perl"),
+    ("dash", "shell"),
+    ("deno", "typescript"),
+    ("escript", "erlang"),
+    ("gmake", "make"),
+    ("jruby", "ruby"),
+    ("ksh", "shell"),
+    ("luajit", "lua"),
+    ("makefile", "make"),
+    ("mksh", "shell"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("pwsh", "powershell"),
+    ("pypy", "python"),
+    ("rscript", "r"),
+    ("runghc", "haskell"),
+    ("runhaskell", "haskell"),
+    ("shell-script", "shell"),
+    ("ts-node", "typescript"),
+];
+
+/// The language a hint name stands for when it is not itself a language name.
+pub fn alias(name: &str) -> Option<&'static str> {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, language)| *language)
+}
+
+/// The first `SNIFF_BYTES` of `content`, cut back to a character boundary.
+pub fn head(content: &str) -> &str {
+    let mut end = content.len().min(SNIFF_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &content[..end]
+}
+
+/// The lowercased name a file's first lines declare: the shebang's interpreter,
+/// else a modeline's filetype or mode.
+pub fn language_hint(content: &str) -> Option<String> {
+    let head = head(content);
+    let first_line = head.lines().next()?;
+    if let Some(interpreter) = shebang_interpreter(first_line) {
+        return Some(interpreter);
+    }
+    head.lines().take(MODELINE_LINES).find_map(modeline_mode)
+}
+
+/// `#!/usr/bin/env -S python3.12 -u` → `python`: the interpreter's file name with
+/// any version suffix dropped, skipping `env` and its options.
+fn shebang_interpreter(line: &str) -> Option<String> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    (!name.is_empty()).then(|| name.to_ascii_lowercase())
+}
+
+/// The filetype of a Vim modeline (`vim: ft=python`, `vim: set filetype=sh :`) or
+/// the mode of an Emacs one (`-*- mode: ruby -*-`, `-*- python -*-`).
+fn modeline_mode(line: &str) -> Option<String> {
+    emacs_mode(line).or_else(|| vim_filetype(line))
+}
+
+fn emacs_mode(line: &str) -> Option<String> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (inner, _) = rest.split_once("-*-")?;
+
+    let mode = if inner.contains(':') {
+        inner.split(';').find_map(|setting| {
+            let (key, value) = setting.split_once(':')?;
+            key.trim().eq_ignore_ascii_case("mode").then_some(value)
+        })?
+    } else {
+        inner
+    };
+    let mode = mode.trim();
+    (!mode.is_empty()).then(|| mode.to_ascii_lowercase())
+}
+
+fn vim_filetype(line: &str) -> Option<String> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(index, _)| *index == 0 || line[..*index].ends_with(char::is_whitespace))
+            .map(|(index, _)| index + marker.len())
+    })?;
+
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| option.strip_prefix("ft=").or_else(|| option.strip_prefix("filetype=")))
+        .filter(|filetype| !filetype.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_shebang_interpreters() {
+        assert_eq!(language_hint("#!/usr/bin/env python3\n").as_deref(), Some("python"));
+        assert_eq!(language_hint("#!/bin/bash -e\n").as_deref(), Some("bash"));
+        assert_eq!(
+            language_hint("#!/usr/bin/env -S VAR=1 node --harmony\n").as_deref(),
+            Some("node")
+        );
+        assert_eq!(language_hint("#!/usr/bin/python3.12").as_deref(), Some("python"));
+        assert_eq!(language_hint("# not a shebang\n"), None);
+    }
+
+    #[test]
+    fn reads_vim_and_emacs_modelines() {
+        assert_eq!(language_hint("# vim: ft=yaml\n").as_deref(), Some("yaml"));
+        assert_eq!(
+            language_hint("<?php\n// vim: set filetype=php ts=4 :\n").as_deref(),
+            Some("php")
+        );
+        assert_eq!(
+            language_hint("# -*- coding: utf-8; mode: Ruby -*-\n").as_deref(),
+            Some("ruby")
+        );
+        assert_eq!(language_hint("/* -*- c++ -*- */\n").as_deref(), Some("c++"));
+        assert_eq!(language_hint("# the envim: ft=no\n"), None);
+    }
+
+    #[test]
+    fn only_looks_at_the_head() {
+        let late = format!("{}\n# vim: ft=python\n", "x\n".repeat(10));
+        assert_eq!(language_hint(&late), None);
+        assert_eq!(head(&"é".repeat(SNIFF_BYTES)).len(), SNIFF_BYTES);
+    }
+}
@@ -1,9 +1,23 @@
 pub mod ast;
+pub mod atomic_write;
 pub mod cli;
+pub mod commented_code;
 pub mod config;
+pub mod explain;
+pub mod file_directive;
+pub mod generated;
+pub mod git;
+pub mod inspect;
+pub mod inventory;
+pub mod journal;
 pub mod languages;
+pub mod line_filter;
+pub mod patch;
 pub mod processor;
+pub mod report;
 pub mod rules;
+pub mod sarif;
+pub mod stats;
 pub mod ui;
 
 pub use processor::{ProcessingOptions, Processor, Removal};
@@ -0,0 +1,128 @@
+//! Line-range restrictions on which comments may be removed.
+//!
+//! A [`LineFilter`] narrows an otherwise normal run to selected lines: the lines
+//! changed since a git revision (`--since`) or an explicit selection
+//! (`--lines`). Comments outside the selection are kept exactly as a
+//! preservation rule would keep them.
+
+use std::ops::RangeInclusive;
+
+/// How a comment's lines must relate to the selected ranges for it to be removable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineFilterMode {
+    /// At least one line of the comment is selected.
+    Intersect,
+    /// Every line of the comment is selected.
+    Contain,
+}
+
+/// A set of 1-based, inclusive line ranges outside which comments are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineFilter {
+    /// Sorted, non-overlapping and non-adjacent ranges.
+    ranges: Vec<RangeInclusive<usize>>,
+    mode: LineFilterMode,
+}
+
+impl LineFilter {
+    /// Build a filter from ranges in any order; overlapping or adjacent ranges
+    /// are merged, and empty ones dropped.
+    pub fn new(ranges: impl IntoIterator<Item = RangeInclusive<usize>>, mode: LineFilterMode) -> Self {
+        let mut sorted: Vec<RangeInclusive<usize>> = ranges.into_iter().filter(|range| !range.is_empty()).collect();
+        sorted.sort_unstable_by_key(|range| *range.start());
+
+        let mut merged: Vec<RangeInclusive<usize>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match merged.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=(*last.end()).max(*range.end());
+                }
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged, mode }
+    }
+
+    /// A filter selecting every line of a file.
+    pub fn all_lines(mode: LineFilterMode) -> Self {
+        Self::new([1..=usize::MAX], mode)
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<usize>] {
+        &self.ranges
+    }
+
+    pub fn mode(&self) -> LineFilterMode {
+        self.mode
+    }
+
+    /// Whether a comment spanning lines `first..=last` (1-based) may be removed.
+    pub fn allows(&self, first: usize, last: usize) -> bool {
+        match self.mode {
+            LineFilterMode::Intersect => self
+                .ranges
+                .iter()
+                .any(|range| *range.start() <= last && first <= *range.end()),
+            LineFilterMode::Contain => self
+                .ranges
+                .iter()
+                .any(|range| range.contains(&first) && range.contains(&last)),
+        }
+    }
+}
+
+/// Parse a 1-based `START:END` line range (both inclusive), or a single `LINE`.
+pub fn parse_line_range(value: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |line: &str| -> Result<usize, String> {
+        match line.trim().parse::<usize>() {
+            Ok(0) => Err("line numbers start at 1".to_string()),
+            Ok(line) => Ok(line),
+            Err(_) => Err(format!("`{line}` is not a line number")),
+        }
+    };
+
+    let (start, end) = match value.split_once(':') {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => {
+            let line = parse(value)?;
+            (line, line)
+        }
+    };
+    if start > end {
+        return Err(format!("range {start}:{end} ends before it starts"));
+    }
+    Ok(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges_and_single_lines() {
+        assert_eq!(parse_line_range("10:40"), Ok(10..=40));
+        assert_eq!(parse_line_range("7"), Ok(7..=7));
+        assert!(parse_line_range("0:3").is_err());
+        assert!(parse_line_range("9:3").is_err());
+        assert!(parse_line_range("a:b").is_err());
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_ranges() {
+        let filter = LineFilter::new([20..=25, 1..=3, 4..=6, 22..=30], LineFilterMode::Intersect);
+        assert_eq!(filter.ranges(), [1..=6, 20..=30]);
+    }
+
+    #[test]
+    fn intersect_and_contain_differ_at_range_edges() {
+        let intersect = LineFilter::new([10..=20], LineFilterMode::Intersect);
+        let contain = LineFilter::new([10..=20], LineFilterMode::Contain);
+
+        assert!(intersect.allows(8, 10));
+        assert!(!contain.allows(8, 10));
+        assert!(contain.allows(10, 20));
+        assert!(!contain.allows(20, 21));
+        assert!(!intersect.allows(21, 22));
+    }
+}
@@ -1,19 +1,37 @@
 mod ast;
+mod atomic_write;
 mod cli;
+mod commented_code;
 mod config;
+mod explain;
+mod file_directive;
+mod generated;
+mod git;
+mod inspect;
+mod inventory;
+mod journal;
 pub mod languages;
+mod line_filter;
+mod patch;
 pub mod processor;
+mod report;
 mod rules;
+mod sarif;
+mod stats;
 mod ui;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, CommentListFormat, OutputFormat};
 use config::ConfigManager;
 use glob::glob;
+use line_filter::{LineFilter, LineFilterMode};
 use once_cell::sync::Lazy;
-use processor::OutputWriter;
+use processor::{OutputWriter, ProcessingOptions, WriteTarget};
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -26,6 +44,13 @@ struct UnsupportedFilesReport {
     samples: Vec<PathBuf>,
 }
 
+/// Exit status for `--check` when at least one file would be modified, kept
+/// distinct from 1 (runtime error) and 2 (usage error) so CI can tell them apart.
+const CHECK_FAILED_EXIT_CODE: i32 = 3;
+
+/// The path argument that selects stdin/stdout streaming mode.
+const STDIN_PATH: &str = "-";
+
 type ImportantRemovalSample = (Arc<PathBuf>, processor::ImportantRemoval);
 
 fn main() -> Result<()> {
@@ -37,6 +62,7 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     if let Some(command) = &cli.command {
+        let config = cli.args.config.as_deref();
         return match command {
             Commands::Init {
                 output,
@@ -44,120 +70,290 @@ fn main() -> Result<()> {
                 comprehensive,
                 interactive,
             } => Cli::handle_init_command(output, *force, *comprehensive, *interactive),
+            Commands::Undo { run_id, list } => Cli::handle_undo_command(run_id.as_deref(), *list),
+            Commands::Languages { json } => Cli::handle_languages_command(*json),
+            Commands::Explain { target } => Cli::handle_explain_command(target, config),
+            Commands::Inspect { file, all } => Cli::handle_inspect_command(file, *all, config),
+            Commands::Stats {
+                paths,
+                json,
+                include_generated,
+            } => run_stats(paths, *json, *include_generated, &cli.args.processing_options(), config),
+            Commands::Config {
+                command: cli::ConfigCommands::Show { path },
+            } => Cli::handle_config_show_command(path, config),
         };
     }
 
     let options = cli.args.processing_options();
 
-    if cli.args.paths.is_empty() {
-        anstream::eprintln!(
-            "{} No input paths specified. Run {} for usage information.",
-            ui::danger("error:"),
+    if cli.args.paths.is_empty() && !cli.args.staged {
+        exit_with_error(format!(
+            "No input paths specified. Run {} for usage information.",
             ui::accent("uncomment --help")
-        );
-        std::process::exit(1);
+        ));
     }
 
-    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let stdin_path = stdin_virtual_path(&cli.args);
 
-    let config_manager = if let Some(config_path) = &cli.args.config {
-        let config = config::Config::from_file(config_path)
-            .with_context(|| format!("Failed to load config file: {}", config_path.display()))?;
+    if !cli.args.lines.is_empty() && !names_single_file(&cli.args) {
+        exit_with_error(format!(
+            "{} applies to one file; pass a single file path (or {}).",
+            ui::accent("--lines"),
+            ui::accent(STDIN_PATH)
+        ));
+    }
 
-        ConfigManager::from_single_config(current_dir, config)?
-    } else {
-        ConfigManager::new(&current_dir).context("Failed to initialize configuration manager")?
-    };
+    // Structured reports and comment inventories own stdout, so human reporting
+    // is silenced as with --quiet.
+    let structured_output = cli.args.output_format != OutputFormat::Text || cli.args.list_comments.is_some();
+    let patch_to_stdout = cli.args.patch.as_deref() == Some(Path::new(STDIN_PATH));
+    if structured_output && patch_to_stdout {
+        exit_with_error(format!(
+            "{} and {} both write to stdout; give the patch a file name.",
+            ui::accent("--output-format"),
+            ui::accent("--patch -")
+        ));
+    }
+    let quiet = cli.args.quiet || structured_output || patch_to_stdout;
+    if structured_output && stdin_path.is_some() && !options.dry_run {
+        exit_with_error(format!(
+            "{} needs stdout for the processed source; add {} to report only.",
+            ui::accent(STDIN_PATH),
+            ui::accent("--dry-run")
+        ));
+    }
 
-    let mut unsupported_report = UnsupportedFilesReport::default();
-    let files = collect_files(&cli.args.paths, &options, &mut unsupported_report)?;
+    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
 
-    print_unsupported_files_report(&unsupported_report, cli.args.verbose);
+    let config_manager = ConfigManager::load(&current_dir, cli.args.config.as_deref())?;
 
-    if files.is_empty() {
-        anstream::eprintln!(
-            "{} No supported files found to process in the specified paths.",
-            ui::warn("!")
-        );
-        anstream::eprintln!("{}", ui::dim(supported_extensions_message()));
-        if options.respect_gitignore {
-            anstream::eprintln!(
-                "{}",
-                ui::dim("Tip: Use --no-gitignore to process files ignored by git.")
-            );
+    if let Some(language) = &options.language {
+        let mut registry = languages::LanguageRegistry::new();
+        registry.register_configured_languages(&config_manager.get_all_languages());
+        if registry.find_language_arc(language).is_none() {
+            exit_with_error(format!(
+                "unknown language {}. Pass a language name or one of its file extensions.",
+                ui::accent(language)
+            ));
         }
-        return Ok(());
     }
 
-    let num_threads = if cli.args.threads == 0 {
-        num_cpus::get()
+    let staged_root = if cli.args.staged {
+        Some(git::repo_root(&current_dir)?)
     } else {
-        cli.args.threads
+        None
     };
 
-    if cli.args.verbose && num_threads > 1 {
-        anstream::println!(
-            "{} {}",
-            ui::dim(ui::BULLET),
-            ui::dim(format!("Using {num_threads} parallel threads"))
-        );
-    }
+    let changed_lines = match &cli.args.since {
+        Some(rev) => {
+            let root = match &staged_root {
+                Some(root) => root.clone(),
+                None => git::repo_root(&current_dir)?,
+            };
+            let changes = git::changed_lines(&root, rev, cli.args.staged)?;
+            Some(ChangedLines::new(&root, changes))
+        }
+        None => None,
+    };
+    let mut line_filters: HashMap<PathBuf, LineFilter> = HashMap::new();
+    let named_files = named_files(&cli.args.paths);
+    let mut generated_files: Vec<GeneratedFile> = Vec::new();
 
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build_global()
-        .context("Failed to initialize thread pool")?;
+    let write_target = if stdin_path.is_some() {
+        WriteTarget::Stdout
+    } else if let Some(root) = &staged_root {
+        WriteTarget::GitIndex { root: root.clone() }
+    } else {
+        WriteTarget::Files
+    };
+    let mut output_writer = OutputWriter::new(options.dry_run, cli.args.verbose, options.show_diff, quiet)
+        .with_target(write_target.clone())
+        .with_write_options(cli.args.write_options());
+    if write_target == WriteTarget::Files && !options.dry_run && !cli.args.no_journal {
+        output_writer = output_writer.with_journal(journal::Journal::new(&current_dir));
+    }
+    let output_writer = Arc::new(output_writer);
 
-    let output_writer = Arc::new(OutputWriter::new(
-        options.dry_run,
-        cli.args.verbose,
-        options.show_diff,
-        cli.args.quiet,
-    ));
+    let (total_files, outcomes) = if let Some(virtual_path) = &stdin_path {
+        (1, vec![Ok(process_stdin(virtual_path, &config_manager, &options)?)])
+    } else {
+        let mut unsupported_report = UnsupportedFilesReport::default();
+        let staged_blobs = match &staged_root {
+            Some(root) => collect_staged_files(
+                root,
+                &current_dir,
+                &cli.args.paths,
+                &options,
+                &config_manager,
+                &mut unsupported_report,
+            )?,
+            None => HashMap::new(),
+        };
+        let mut files = if staged_root.is_some() {
+            let mut files: Vec<PathBuf> = staged_blobs.keys().cloned().collect();
+            files.sort();
+            files
+        } else {
+            collect_files(&cli.args.paths, &options, &config_manager, &mut unsupported_report)?
+        };
 
-    let total_files = files.len();
+        print_unsupported_files_report(&unsupported_report, cli.args.verbose);
+        if !cli.args.include_generated {
+            generated_files = skip_generated_files(&mut files, &current_dir, &staged_blobs);
+        }
 
-    let progress = if total_files >= ui::PROGRESS_MIN_FILES && !cli.args.verbose {
-        ui::progress_bar(total_files as u64)
-    } else {
-        indicatif::ProgressBar::hidden()
-    };
+        if let Some(changed) = &changed_lines {
+            files.retain(|file| match changed.filter_for(file) {
+                Some(filter) => {
+                    line_filters.insert(file.clone(), filter);
+                    true
+                }
+                None => false,
+            });
+        }
 
-    let process_file = |file_path: &PathBuf| -> Option<processor::ProcessedFile> {
-        let mut proc = processor::Processor::new_with_config(&config_manager);
-        let result = match proc.process_file_with_config(file_path, &config_manager, Some(&options)) {
-            Ok(mut pf) => {
-                pf.modified = pf.original_content != pf.processed_content;
-                Some(pf)
+        if files.is_empty() && changed_lines.is_some() {
+            if !quiet {
+                anstream::println!(
+                    "{} {}",
+                    ui::dim(ui::BULLET),
+                    ui::dim("No changed lines to process in the specified paths.")
+                );
             }
-            Err(e) => {
-                progress.suspend(|| {
-                    anstream::eprintln!("{} processing {}: {e}", ui::danger("error"), ui::path(file_path));
-                    if cli.args.verbose {
-                        anstream::eprintln!("  {}", ui::dim(format!("Full error: {e:?}")));
-                    }
-                });
-                None
+            if !structured_output {
+                return Ok(());
             }
+        } else if files.is_empty() {
+            anstream::eprintln!(
+                "{} No supported files found to process in the specified paths.",
+                ui::warn("!")
+            );
+            anstream::eprintln!("{}", ui::dim(supported_extensions_message()));
+            if options.respect_gitignore {
+                anstream::eprintln!(
+                    "{}",
+                    ui::dim("Tip: Use --no-gitignore to process files ignored by git.")
+                );
+            }
+            if !structured_output {
+                return Ok(());
+            }
+        }
+
+        let num_threads = if cli.args.threads == 0 {
+            num_cpus::get()
+        } else {
+            cli.args.threads
         };
-        progress.inc(1);
-        result
-    };
 
-    let results: Vec<processor::ProcessedFile> = if num_threads == 1 {
-        files.iter().filter_map(process_file).collect()
-    } else {
-        files.par_iter().filter_map(process_file).collect()
-    };
+        if cli.args.verbose && num_threads > 1 {
+            anstream::println!(
+                "{} {}",
+                ui::dim(ui::BULLET),
+                ui::dim(format!("Using {num_threads} parallel threads"))
+            );
+        }
+
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build_global()
+            .context("Failed to initialize thread pool")?;
+
+        if let Some(format) = cli.args.list_comments {
+            let list_file = |file_path: &PathBuf| -> Vec<inventory::CommentRecord> {
+                let mut proc = processor::Processor::new_with_config(&config_manager);
+                let options = options_for(&options, &line_filters, &named_files, file_path);
+                let content = match staged_blobs.get(file_path) {
+                    Some(blob) => git::read_blob(&current_dir, blob),
+                    None => std::fs::read_to_string(file_path)
+                        .with_context(|| format!("Failed to read file: {}", file_path.display())),
+                };
+                match content
+                    .and_then(|content| proc.list_comments(file_path, &content, &config_manager, Some(&options)))
+                {
+                    Ok(records) => records,
+                    Err(e) => {
+                        anstream::eprintln!("{} processing {}: {e}", ui::danger("error"), ui::path(file_path));
+                        Vec::new()
+                    }
+                }
+            };
+            let records: Vec<inventory::CommentRecord> = if num_threads == 1 {
+                files.iter().flat_map(list_file).collect()
+            } else {
+                files.par_iter().flat_map_iter(list_file).collect()
+            };
+
+            let mut out = std::io::stdout().lock();
+            return match format {
+                CommentListFormat::Json => inventory::write_json(&mut out, &records),
+                CommentListFormat::Csv => inventory::write_csv(&mut out, &records),
+            };
+        }
+
+        let total_files = files.len();
+
+        let progress = if total_files >= ui::PROGRESS_MIN_FILES && !cli.args.verbose {
+            ui::progress_bar(total_files as u64)
+        } else {
+            indicatif::ProgressBar::hidden()
+        };
+
+        let process_file = |file_path: &PathBuf| -> report::FileOutcome {
+            let mut proc = processor::Processor::new_with_config(&config_manager);
+            let options = options_for(&options, &line_filters, &named_files, file_path);
+            let processed = match staged_blobs.get(file_path) {
+                Some(blob) => git::read_blob(&current_dir, blob).and_then(|content| {
+                    proc.process_source_with_config(file_path, content, &config_manager, Some(&options))
+                }),
+                None => proc.process_file_with_config(file_path, &config_manager, Some(&options)),
+            };
+            let result = match processed {
+                Ok(mut pf) => {
+                    pf.modified = pf.original_content != pf.processed_content;
+                    Ok(pf)
+                }
+                Err(e) => {
+                    progress.suspend(|| {
+                        anstream::eprintln!("{} processing {}: {e}", ui::danger("error"), ui::path(file_path));
+                        if cli.args.verbose {
+                            anstream::eprintln!("  {}", ui::dim(format!("Full error: {e:?}")));
+                        }
+                    });
+                    Err(report::ProcessingFailure {
+                        path: file_path.clone(),
+                        message: e.to_string(),
+                    })
+                }
+            };
+            progress.inc(1);
+            result
+        };
+
+        let outcomes: Vec<report::FileOutcome> = if num_threads == 1 {
+            files.iter().map(process_file).collect()
+        } else {
+            files.par_iter().map(process_file).collect()
+        };
 
-    progress.finish_and_clear();
+        progress.finish_and_clear();
+
+        (total_files, outcomes)
+    };
 
     let mut modified_files = 0usize;
     let mut comments_removed_total = 0usize;
     let mut important_removal_count = 0usize;
     let mut important_removal_samples: Vec<ImportantRemovalSample> = Vec::new();
+    let mut failed_files = 0usize;
+
+    for outcome in &outcomes {
+        let Ok(processed_file) = outcome else {
+            failed_files += 1;
+            continue;
+        };
 
-    for processed_file in &results {
         if processed_file.modified {
             modified_files += 1;
             comments_removed_total += processed_file.comments_removed;
@@ -178,9 +374,59 @@ fn main() -> Result<()> {
         output_writer.write_file(processed_file)?;
     }
 
-    output_writer.print_summary(total_files, modified_files, comments_removed_total);
+    if let Some(patch_path) = &cli.args.patch {
+        let patch: String = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.as_ref().ok())
+            .map(patch::unified_diff)
+            .collect();
+        if patch_to_stdout {
+            use std::io::Write;
+            std::io::stdout()
+                .lock()
+                .write_all(patch.as_bytes())
+                .context("Failed to write patch to stdout")?;
+        } else {
+            std::fs::write(patch_path, &patch)
+                .with_context(|| format!("Failed to write patch: {}", patch_path.display()))?;
+            if !quiet {
+                anstream::println!(
+                    "{} {} {}",
+                    ui::success(ui::CHECK),
+                    ui::success("Wrote patch:"),
+                    ui::path(patch_path)
+                );
+            }
+        }
+    }
 
-    if comments_removed_total > 0 && !cli.args.quiet {
+    let summary = report::Summary {
+        total_files,
+        modified_files,
+        comments_removed: comments_removed_total,
+        failed_files,
+        dry_run: options.dry_run,
+        skipped_generated: generated_files.iter().map(|(path, _)| path.clone()).collect(),
+    };
+    match cli.args.output_format {
+        OutputFormat::Text if patch_to_stdout => {}
+        OutputFormat::Text => {
+            output_writer.print_summary(total_files, modified_files, comments_removed_total);
+            if !quiet {
+                print_generated_files_report(&generated_files, cli.args.verbose);
+            }
+        }
+        OutputFormat::Json => report::write_json(&mut std::io::stdout().lock(), &outcomes, &summary)?,
+        OutputFormat::Ndjson => report::write_ndjson(&mut std::io::stdout().lock(), &outcomes, &summary)?,
+        OutputFormat::Sarif => {
+            let files = plan_sarif_files(&outcomes, &config_manager, &options, &line_filters, &named_files)?;
+            let failures: Vec<&report::ProcessingFailure> =
+                outcomes.iter().filter_map(|outcome| outcome.as_ref().err()).collect();
+            sarif::write_sarif(&mut std::io::stdout().lock(), &files, &failures)?
+        }
+    }
+
+    if comments_removed_total > 0 && !quiet {
         anstream::eprintln!();
         anstream::eprintln!(
             "{}",
@@ -195,7 +441,20 @@ fn main() -> Result<()> {
         anstream::eprintln!("{}", ui::dim("     Preview changes first with --dry-run --diff."));
     }
 
-    if important_removal_count > 0 && !cli.args.quiet {
+    if let Some(journal) = output_writer.journal()
+        && journal.has_entries()
+        && !quiet
+    {
+        anstream::eprintln!(
+            "{}",
+            ui::dim(format!(
+                "     Originals saved; revert this run with `uncomment undo {}`.",
+                journal.run_id()
+            ))
+        );
+    }
+
+    if important_removal_count > 0 && !quiet {
         anstream::eprintln!(
             "{} removed {} potentially important comment(s). Re-run with {} to inspect.",
             ui::warn("warning:"),
@@ -219,12 +478,249 @@ fn main() -> Result<()> {
         }
     }
 
+    if cli.args.check && modified_files > 0 {
+        anstream::eprintln!(
+            "{} {} file(s) would be modified. Run without {} to apply.",
+            ui::danger("error:"),
+            ui::danger(modified_files),
+            ui::accent("--check")
+        );
+        std::process::exit(CHECK_FAILED_EXIT_CODE);
+    }
+
     Ok(())
 }
 
+/// Validate stdin mode and return the virtual path stdin should be processed as.
+///
+/// Exits with a usage error when `-` is mixed with other paths or given without
+/// `--stdin-filepath`, since there is nothing to detect the language from.
+fn stdin_virtual_path(args: &cli::ProcessArgs) -> Option<PathBuf> {
+    let reads_stdin = args.paths.iter().any(|path| path == STDIN_PATH);
+
+    match (&args.stdin_filepath, reads_stdin) {
+        (Some(_), true) if args.paths.len() > 1 => exit_with_error(format!(
+            "{} cannot be combined with other paths.",
+            ui::accent(STDIN_PATH)
+        )),
+        (Some(name), true) => Some(name.clone()),
+        (None, true) => exit_with_error(format!(
+            "reading stdin requires {} to pick the language.",
+            ui::accent("--stdin-filepath <NAME>")
+        )),
+        (Some(_), false) => exit_with_error(format!(
+            "{} is only valid with {} as the path.",
+            ui::accent("--stdin-filepath"),
+            ui::accent(STDIN_PATH)
+        )),
+        (None, false) => None,
+    }
+}
+
+/// Whether the run targets exactly one file, as line numbers only make sense then.
+fn names_single_file(args: &cli::ProcessArgs) -> bool {
+    match args.paths.as_slice() {
+        [path] => path == STDIN_PATH || Path::new(path).is_file(),
+        _ => false,
+    }
+}
+
+/// Run the stdin buffer through the processor under its `--stdin-filepath` name.
+fn process_stdin(
+    virtual_path: &Path,
+    config_manager: &ConfigManager,
+    options: &ProcessingOptions,
+) -> Result<processor::ProcessedFile> {
+    use std::io::Read;
+
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .context("Failed to read stdin")?;
+
+    let mut proc = processor::Processor::new_with_config(config_manager);
+    let mut processed = proc
+        .process_source_with_config(virtual_path, content, config_manager, Some(options))
+        .with_context(|| format!("Failed to process stdin as {}", virtual_path.display()))?;
+    processed.modified = processed.original_content != processed.processed_content;
+    Ok(processed)
+}
+
+/// Measure every supported file under `paths` for `uncomment stats`.
+fn run_stats(
+    paths: &[String],
+    json: bool,
+    include_generated: bool,
+    options: &ProcessingOptions,
+    config: Option<&Path>,
+) -> Result<()> {
+    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let config_manager = ConfigManager::load(&current_dir, config)?;
+
+    let mut unsupported_report = UnsupportedFilesReport::default();
+    let mut files = collect_files(paths, options, &config_manager, &mut unsupported_report)?;
+    if !include_generated {
+        skip_generated_files(&mut files, &current_dir, &HashMap::new());
+    }
+    if files.is_empty() {
+        anstream::eprintln!(
+            "{} No supported files found to measure in the specified paths.",
+            ui::warn("!")
+        );
+    }
+
+    let named_files = named_files(paths);
+    let results: Vec<Result<stats::FileStats>> = files
+        .par_iter()
+        .map(|file_path| {
+            let mut proc = processor::Processor::new_with_config(&config_manager);
+            let options = options_for(options, &HashMap::new(), &named_files, file_path);
+            std::fs::read_to_string(file_path)
+                .with_context(|| format!("Failed to read file: {}", file_path.display()))
+                .and_then(|content| proc.comment_stats(file_path, &content, &config_manager, Some(&options)))
+        })
+        .collect();
+
+    let mut report = stats::StatsReport::default();
+    for (file_path, result) in files.iter().zip(results) {
+        match result {
+            Ok(mut file_stats) => {
+                if let Ok(relative) = file_path.strip_prefix(&current_dir) {
+                    file_stats.path = relative.to_path_buf();
+                }
+                report.add(&file_stats)
+            }
+            Err(e) => anstream::eprintln!("{} processing {}: {e}", ui::danger("error"), ui::path(file_path)),
+        }
+    }
+    Cli::handle_stats_command(&report, json)
+}
+
+/// Plan comment-level removals for every modified file, since SARIF locates the
+/// comment token itself rather than the (possibly line-expanded) deleted range.
+fn plan_sarif_files<'a>(
+    outcomes: &'a [report::FileOutcome],
+    config_manager: &ConfigManager,
+    options: &ProcessingOptions,
+    line_filters: &HashMap<PathBuf, LineFilter>,
+    named_files: &HashSet<PathBuf>,
+) -> Result<Vec<sarif::SarifFile<'a>>> {
+    outcomes
+        .par_iter()
+        .filter_map(|outcome| outcome.as_ref().ok())
+        .filter(|processed| processed.modified)
+        .map(|processed| {
+            let mut proc = processor::Processor::new_with_config(config_manager);
+            let options = options_for(options, line_filters, named_files, &processed.path);
+            let (_, config) = proc.resolve_config(
+                &processed.path,
+                &processed.original_content,
+                config_manager,
+                Some(&options),
+            )?;
+            let removals = proc.plan_removals(&processed.original_content, &processed.path, &config)?;
+            Ok(sarif::SarifFile {
+                path: &processed.path,
+                content: &processed.original_content,
+                removals,
+                important_removals: &processed.important_removals,
+            })
+        })
+        .collect()
+}
+
+/// `options` narrowed to `path`'s own line filter, if it has one, and without
+/// `--language` unless `path` is one of the `named_files`.
+fn options_for<'a>(
+    options: &'a ProcessingOptions,
+    line_filters: &HashMap<PathBuf, LineFilter>,
+    named_files: &HashSet<PathBuf>,
+    path: &Path,
+) -> Cow<'a, ProcessingOptions> {
+    let keeps_language = options.language.is_none() || named_files.contains(path);
+    match line_filters.get(path) {
+        None if keeps_language => Cow::Borrowed(options),
+        filter => Cow::Owned(ProcessingOptions {
+            line_filter: filter.cloned().or_else(|| options.line_filter.clone()),
+            language: options.language.clone().filter(|_| keeps_language),
+            ..options.clone()
+        }),
+    }
+}
+
+/// The files named directly on the command line. `--language` only applies to
+/// these (and stdin); files found by walking a directory or expanding a glob
+/// keep their detected language, so a README or YAML file is never parsed as
+/// the forced language.
+fn named_files(paths: &[String]) -> HashSet<PathBuf> {
+    paths.iter().map(PathBuf::from).filter(|path| path.is_file()).collect()
+}
+
+/// Lines changed since the `--since` revision, keyed by absolute path.
+struct ChangedLines(HashMap<PathBuf, Vec<RangeInclusive<usize>>>);
+
+impl ChangedLines {
+    /// `changes` holds paths relative to the repository `root`.
+    fn new(root: &Path, changes: HashMap<PathBuf, Vec<RangeInclusive<usize>>>) -> Self {
+        Self(
+            changes
+                .into_iter()
+                .map(|(path, ranges)| (root.join(path), ranges))
+                .collect(),
+        )
+    }
+
+    /// A filter keeping every comment that touches no changed line of `path`,
+    /// or `None` when `path` has not changed at all.
+    fn filter_for(&self, path: &Path) -> Option<LineFilter> {
+        let absolute = std::fs::canonicalize(path)
+            .or_else(|_| std::path::absolute(path))
+            .ok()?;
+        let ranges = self.0.get(&absolute)?;
+        Some(LineFilter::new(ranges.iter().cloned(), LineFilterMode::Intersect))
+    }
+}
+
+fn exit_with_error(message: impl std::fmt::Display) -> ! {
+    anstream::eprintln!("{} {message}", ui::danger("error:"));
+    std::process::exit(1);
+}
+
+/// Staged files matching `pathspecs`, keyed by display path (relative to the
+/// current directory when inside it) and mapped to the staged blob to read.
+fn collect_staged_files(
+    root: &Path,
+    current_dir: &Path,
+    pathspecs: &[String],
+    options: &ProcessingOptions,
+    config_manager: &ConfigManager,
+    unsupported: &mut UnsupportedFilesReport,
+) -> Result<HashMap<PathBuf, String>> {
+    let mut files = HashMap::new();
+    for staged in git::staged_files(current_dir, pathspecs)? {
+        let absolute = root.join(&staged.path);
+        let path = absolute
+            .strip_prefix(current_dir)
+            .map(Path::to_path_buf)
+            .unwrap_or(absolute);
+
+        if journal::is_journal_path(&path) {
+            continue;
+        } else if (options.language.is_some() && pathspecs.iter().any(|spec| Path::new(spec) == path))
+            || is_supported_file(&path, config_manager)
+        {
+            files.insert(path, staged.blob);
+        } else {
+            record_unsupported_file(&path, unsupported);
+        }
+    }
+    Ok(files)
+}
+
 fn collect_files(
     paths: &[String],
-    options: &processor::ProcessingOptions,
+    options: &ProcessingOptions,
+    config_manager: &ConfigManager,
     unsupported: &mut UnsupportedFilesReport,
 ) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
@@ -233,16 +729,18 @@ fn collect_files(
         let path = Path::new(path_pattern);
 
         if path.is_file() {
-            if has_supported_extension(path) {
+            if journal::is_journal_path(path) {
+                continue;
+            } else if options.language.is_some() || is_supported_file(path, config_manager) {
                 files.push(path.to_path_buf());
             } else {
                 record_unsupported_file(path, unsupported);
             }
         } else if path.is_dir() {
             let pattern = format!("{}/**/*", path.display());
-            collect_from_pattern(&pattern, &mut files, options, unsupported)?
+            collect_from_pattern(&pattern, &mut files, options, config_manager, unsupported)?
         } else {
-            collect_from_pattern(path_pattern, &mut files, options, unsupported)?
+            collect_from_pattern(path_pattern, &mut files, options, config_manager, unsupported)?
         }
     }
 
@@ -255,7 +753,8 @@ fn collect_files(
 fn collect_from_pattern(
     pattern: &str,
     files: &mut Vec<PathBuf>,
-    options: &processor::ProcessingOptions,
+    options: &ProcessingOptions,
+    config_manager: &ConfigManager,
     unsupported: &mut UnsupportedFilesReport,
 ) -> Result<()> {
     if options.respect_gitignore {
@@ -304,6 +803,7 @@ fn collect_from_pattern(
             .git_exclude(true)
             .parents(true)
             .require_git(false)
+            .filter_entry(|entry| entry.file_name() != journal::JOURNAL_DIR && !is_vcs_dir_name(entry.file_name()))
             .build();
 
         for entry in walker {
@@ -318,7 +818,7 @@ fn collect_from_pattern(
                     }
 
                     if path.is_file() {
-                        if has_supported_extension(path) {
+                        if is_supported_file(path, config_manager) {
                             files.push(path.to_path_buf());
                         } else {
                             record_unsupported_file(path, unsupported);
@@ -332,8 +832,8 @@ fn collect_from_pattern(
         for entry in glob(pattern).context("Failed to parse glob pattern")? {
             match entry {
                 Ok(path) => {
-                    if path.is_file() {
-                        if has_supported_extension(&path) {
+                    if path.is_file() && !journal::is_journal_path(&path) && !is_vcs_path(&path) {
+                        if is_supported_file(&path, config_manager) {
                             files.push(path);
                         } else {
                             record_unsupported_file(&path, unsupported);
@@ -347,6 +847,20 @@ fn collect_from_pattern(
     Ok(())
 }
 
+/// Version control metadata directories, never walked into: their hooks are
+/// extensionless scripts that shebang detection would otherwise pick up.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn"];
+
+fn is_vcs_dir_name(name: &std::ffi::OsStr) -> bool {
+    VCS_DIRS.iter().any(|dir| name == *dir)
+}
+
+/// Whether `path` lies inside a [`VCS_DIRS`] directory.
+fn is_vcs_path(path: &Path) -> bool {
+    path.components()
+        .any(|component| is_vcs_dir_name(component.as_os_str()))
+}
+
 fn record_unsupported_file(path: &Path, report: &mut UnsupportedFilesReport) {
     report.total += 1;
 
@@ -364,6 +878,78 @@ fn record_unsupported_file(path: &Path, report: &mut UnsupportedFilesReport) {
     }
 }
 
+/// A generated file left out of a run, with why it counts as generated.
+type GeneratedFile = (PathBuf, generated::GeneratedReason);
+
+/// Take generated files out of `files`. `.gitattributes` only counts inside a
+/// git repository; elsewhere the name and header decide. Files in
+/// `staged_blobs` are judged by their staged content, which is what a
+/// `--staged` run rewrites.
+fn skip_generated_files(
+    files: &mut Vec<PathBuf>,
+    current_dir: &Path,
+    staged_blobs: &HashMap<PathBuf, String>,
+) -> Vec<GeneratedFile> {
+    if files.is_empty() {
+        return Vec::new();
+    }
+    // A failed lookup means no attributes, not a failed run.
+    let attributes = if git::repo_root(current_dir).is_ok() {
+        git::generated_attributes(current_dir, files).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let mut skipped = Vec::new();
+    let mut index = 0;
+    files.retain(|file| {
+        let reason = if attributes.get(index).copied().unwrap_or(false) {
+            Some(generated::GeneratedReason::GitAttributes)
+        } else if let Some(blob) = staged_blobs.get(file) {
+            // An unreadable blob is reported when it is processed.
+            git::read_blob(current_dir, blob)
+                .ok()
+                .and_then(|content| generated::detect(file, &content))
+        } else {
+            generated::detect_file(file)
+        };
+        index += 1;
+        match reason {
+            Some(reason) => {
+                skipped.push((file.clone(), reason));
+                false
+            }
+            None => true,
+        }
+    });
+    skipped
+}
+
+fn print_generated_files_report(files: &[GeneratedFile], verbose: bool) {
+    if files.is_empty() {
+        return;
+    }
+
+    anstream::println!(
+        "{}",
+        ui::dim(format!(
+            "Skipped {} generated file(s); process them with --include-generated:",
+            files.len()
+        ))
+    );
+    const MAX_SHOWN: usize = 10;
+    let shown = if verbose { files.len() } else { MAX_SHOWN };
+    for (path, reason) in files.iter().take(shown) {
+        anstream::println!("  {} {}", ui::path(path), ui::dim(format!("({reason})")));
+    }
+    if files.len() > shown {
+        anstream::println!(
+            "  {}",
+            ui::dim(format!("... and {} more (--verbose lists all)", files.len() - shown))
+        );
+    }
+}
+
 fn print_unsupported_files_report(report: &UnsupportedFilesReport, verbose: bool) {
     if report.total == 0 {
         return;
@@ -396,6 +982,34 @@ fn print_unsupported_files_report(report: &UnsupportedFilesReport, verbose: bool
     }
 }
 
+/// Whether `path` can be processed: a `[patterns]` entry forces a language for
+/// it, or its name or first bytes identify a known language. `--language` is
+/// checked by the callers, since it only covers files named on the command line.
+fn is_supported_file(path: &Path, config_manager: &ConfigManager) -> bool {
+    config_manager.language_for_path(path).is_some() || has_supported_extension(path) || has_supported_content(path)
+}
+
+/// Whether a shebang or modeline in the first few hundred bytes of `path` names
+/// a known language.
+fn has_supported_content(path: &Path) -> bool {
+    use std::io::Read;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut head = Vec::with_capacity(languages::sniff::SNIFF_BYTES);
+    if file
+        .take(languages::sniff::SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .is_err()
+    {
+        return false;
+    }
+    DEFAULT_LANGUAGE_REGISTRY
+        .detect_language_from_content(&String::from_utf8_lossy(&head))
+        .is_some()
+}
+
 fn has_supported_extension(path: &Path) -> bool {
     DEFAULT_LANGUAGE_REGISTRY.detect_language(path).is_some()
 }
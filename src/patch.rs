@@ -0,0 +1,276 @@
+//! Standard unified diffs (`--patch`) that `git apply` and `patch -p1` accept.
+//!
+//! Like [`OutputWriter`](crate::processor::OutputWriter)'s preview diff, hunks are
+//! derived from [`ProcessedFile::removed_ranges`] rather than by diffing two
+//! texts: only lines a range touches change, and their new text is the original
+//! with the covered bytes cut out.
+
+use crate::processor::{ProcessedFile, cut_ranges, merge_ranges};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Unchanged lines shown around each change, matching `diff -u` and `git diff`.
+const CONTEXT: usize = 3;
+
+/// A run of adjacent original lines rewritten together, and their replacement.
+struct Change {
+    /// 0-based index of the first original line.
+    first: usize,
+    /// Number of original lines replaced.
+    old_len: usize,
+    /// Replacement lines, each keeping its own line terminator.
+    new_lines: Vec<String>,
+}
+
+/// Render `processed` as a `diff --git` patch, or an empty string when it is unchanged.
+pub fn unified_diff(processed: &ProcessedFile) -> String {
+    unified_diff_at(processed, &patch_path(&processed.path))
+}
+
+/// Like [`unified_diff`], but with `path` used verbatim in the `a/` and `b/` headers.
+pub fn unified_diff_at(processed: &ProcessedFile, path: &str) -> String {
+    let content = processed.original_content.as_str();
+    let merged = merge_ranges(&processed.removed_ranges);
+    if merged.is_empty() {
+        return String::new();
+    }
+
+    let lines: Vec<(usize, &str)> = content
+        .split_inclusive('\n')
+        .scan(0usize, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect();
+
+    let changes = collect_changes(content, &lines, &merged);
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "diff --git a/{path} b/{path}");
+    let _ = writeln!(out, "--- a/{path}");
+    let _ = writeln!(out, "+++ b/{path}");
+
+    // Group changes whose context windows touch into a single hunk.
+    let mut line_delta: isize = 0;
+    let mut index = 0;
+    while index < changes.len() {
+        let mut last = index;
+        while last + 1 < changes.len() {
+            let gap_start = changes[last].first + changes[last].old_len;
+            if changes[last + 1].first - gap_start > 2 * CONTEXT {
+                break;
+            }
+            last += 1;
+        }
+
+        let old_start = changes[index].first.saturating_sub(CONTEXT);
+        let old_end = (changes[last].first + changes[last].old_len + CONTEXT).min(lines.len());
+        let mut body = String::new();
+        let mut new_len = 0usize;
+        let mut cursor = old_start;
+
+        for change in &changes[index..=last] {
+            for (_, line) in &lines[cursor..change.first] {
+                push_line(&mut body, ' ', line);
+                new_len += 1;
+            }
+            for (_, line) in &lines[change.first..change.first + change.old_len] {
+                push_line(&mut body, '-', line);
+            }
+            for line in &change.new_lines {
+                push_line(&mut body, '+', line);
+                new_len += 1;
+            }
+            cursor = change.first + change.old_len;
+        }
+        for (_, line) in &lines[cursor..old_end] {
+            push_line(&mut body, ' ', line);
+            new_len += 1;
+        }
+
+        let old_len = old_end - old_start;
+        let new_start = (old_start as isize + line_delta) as usize;
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)
+        );
+        out.push_str(&body);
+        line_delta += new_len as isize - old_len as isize;
+        index = last + 1;
+    }
+
+    out
+}
+
+/// Split the original lines touched by `merged` into independent changes.
+fn collect_changes(content: &str, lines: &[(usize, &str)], merged: &[(usize, usize)]) -> Vec<Change> {
+    let touched: Vec<bool> = lines
+        .iter()
+        .map(|&(start, line)| {
+            let end = start + line.len();
+            merged.iter().any(|&(from, to)| from < end && to > start)
+        })
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut line = 0;
+    while line < lines.len() {
+        if !touched[line] {
+            line += 1;
+            continue;
+        }
+
+        let first = line;
+        let mut last = line;
+        loop {
+            while last + 1 < lines.len() && touched[last + 1] {
+                last += 1;
+            }
+            let start = lines[first].0;
+            let end = lines[last].0 + lines[last].1.len();
+            let new_text = cut_ranges(content, start, end, merged);
+            // A cut that swallowed this run's final newline joins it to the next
+            // line, so that line belongs to the same change.
+            if !new_text.is_empty() && !new_text.ends_with('\n') && last + 1 < lines.len() {
+                last += 1;
+                continue;
+            }
+
+            let new_lines: Vec<String> = new_text.split_inclusive('\n').map(str::to_string).collect();
+            if new_lines.len() != last - first + 1
+                || new_lines
+                    .iter()
+                    .zip(&lines[first..=last])
+                    .any(|(new, (_, old))| new != old)
+            {
+                changes.push(Change {
+                    first,
+                    old_len: last - first + 1,
+                    new_lines,
+                });
+            }
+            break;
+        }
+        line = last + 1;
+    }
+    changes
+}
+
+fn push_line(body: &mut String, marker: char, line: &str) {
+    body.push(marker);
+    body.push_str(line);
+    if !line.ends_with('\n') {
+        body.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// `start,len` with a 1-based start; an empty side names the line before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{start},0")
+    } else {
+        format!("{},{len}", start + 1)
+    }
+}
+
+/// Repository-style path for `a/` and `b/` headers: relative to the current
+/// directory when possible, with forward slashes and no leading `./`.
+fn patch_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path);
+    let normalized = relative.to_string_lossy().replace('\\', "/");
+    normalized.trim_start_matches("./").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn processed(source: &str, removed: &[&str]) -> ProcessedFile {
+        let removed_ranges: Vec<(usize, usize)> = removed
+            .iter()
+            .map(|text| {
+                let start = source.find(text).unwrap();
+                (start, start + text.len())
+            })
+            .collect();
+        let merged = merge_ranges(&removed_ranges);
+        let processed_content = cut_ranges(source, 0, source.len(), &merged);
+        ProcessedFile {
+            path: PathBuf::from("src/lib.rs"),
+            language: "rust".to_string(),
+            original_content: source.to_string(),
+            processed_content,
+            modified: true,
+            comments_removed: removed.len(),
+            removed_comments: Vec::new(),
+            removed_ranges,
+            important_removals: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn renders_whole_line_and_trailing_removals() {
+        let source = "// header\nfn a() {}\nfn b() {} // trailing\nfn c() {}\n";
+        let patch = unified_diff(&processed(source, &["// header\n", " // trailing"]));
+
+        assert_eq!(
+            patch,
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -1,4 +1,3 @@\n\
+             -// header\n\
+             \x20fn a() {}\n\
+             -fn b() {} // trailing\n\
+             +fn b() {}\n\
+             \x20fn c() {}\n"
+        );
+    }
+
+    #[test]
+    fn splits_distant_changes_into_hunks_with_shifted_new_starts() {
+        let mut source = String::from("// one\n");
+        for i in 0..10 {
+            source.push_str(&format!("let v{i} = {i};\n"));
+        }
+        source.push_str("// two\nlet end = 0;\n");
+        let patch = unified_diff(&processed(&source, &["// one\n", "// two\n"]));
+
+        assert!(patch.contains("@@ -1,4 +1,3 @@\n"), "{patch}");
+        assert!(patch.contains("@@ -9,5 +8,4 @@\n"), "{patch}");
+    }
+
+    #[test]
+    fn marks_missing_final_newline() {
+        let source = "fn a() {}\nfn b() {} // gone";
+        let patch = unified_diff(&processed(source, &[" // gone"]));
+
+        assert!(
+            patch.ends_with(
+                "-fn b() {} // gone\n\\ No newline at end of file\n+fn b() {}\n\\ No newline at end of file\n"
+            ),
+            "{patch}"
+        );
+    }
+
+    #[test]
+    fn file_that_becomes_empty_has_empty_new_side() {
+        let patch = unified_diff(&processed("// only\n", &["// only\n"]));
+        assert!(patch.contains("@@ -1,1 +0,0 @@\n-// only\n"), "{patch}");
+    }
+
+    #[test]
+    fn unchanged_file_produces_no_patch() {
+        assert!(unified_diff(&processed("fn a() {}\n", &[])).is_empty());
+    }
+}
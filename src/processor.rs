@@ -1,11 +1,22 @@
-use crate::ast::visitor::{CommentInfo, CommentVisitor};
-use crate::config::{ConfigManager, ResolvedConfig};
+use crate::ast::visitor::{CommentInfo, CommentVisitor, Preservation};
+use crate::commented_code::{CommentKind, classify_comments};
+use crate::config::{ConfigManager, ResolvedConfig, SettingSource};
+use crate::explain::Explanation;
+use crate::file_directive::FileDirectives;
+use crate::inspect::{InspectedNode, Inspection, NodeComment};
+use crate::inventory::CommentRecord;
+use crate::languages::config::LanguageConfig;
 use crate::languages::registry::LanguageRegistry;
+use crate::line_filter::LineFilter;
 use crate::rules::preservation::PreservationRule;
+use crate::stats::{CommentStats, FileStats, KeptBy, count_lines};
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::Path;
-use tree_sitter::Parser;
+use std::sync::Arc;
+use tree_sitter::{Parser, Tree};
 
 #[derive(Debug, Clone)]
 pub struct ProcessingOptions {
@@ -14,10 +25,87 @@ pub struct ProcessingOptions {
     pub remove_doc: bool,
     pub custom_preserve_patterns: Vec<String>,
     pub use_default_ignores: bool,
+    /// Remove only commented-out code, keeping prose comments.
+    pub only_commented_code: bool,
     pub dry_run: bool,
     pub show_diff: bool,
     pub respect_gitignore: bool,
     pub traverse_git_repos: bool,
+    /// Only remove comments on these lines; everything else is kept.
+    pub line_filter: Option<LineFilter>,
+    /// Parse every file as this language instead of detecting it.
+    pub language: Option<String>,
+}
+
+impl ProcessingOptions {
+    /// Layer these CLI options over a config resolved from files.
+    pub fn apply_to(&self, config: &mut ResolvedConfig) {
+        if self.remove_doc {
+            config.remove_docs = true;
+            config.sources.remove_docs = SettingSource::Cli;
+        }
+        // Apply flags one-directionally so an unset flag never clobbers config-file
+        // values: only --no-default-ignores forces this off (see issue #106).
+        if !self.use_default_ignores {
+            config.use_default_ignores = false;
+            config.sources.use_default_ignores = SettingSource::Cli;
+        }
+        if self.only_commented_code {
+            config.only_commented_code = true;
+            config.sources.only_commented_code = SettingSource::Cli;
+        }
+        if self.remove_todo {
+            config.remove_todos = true;
+            config.sources.remove_todos = SettingSource::Cli;
+        }
+        if self.remove_fixme {
+            config.remove_fixme = true;
+            config.sources.remove_fixme = SettingSource::Cli;
+        }
+        if !self.custom_preserve_patterns.is_empty() {
+            config
+                .preserve_patterns
+                .extend(self.custom_preserve_patterns.iter().cloned());
+            config.sources.preserve_patterns.extend(
+                self.custom_preserve_patterns
+                    .iter()
+                    .map(|pattern| (pattern.clone(), SettingSource::Cli)),
+            );
+        }
+        if !self.respect_gitignore {
+            config.respect_gitignore = false;
+            config.sources.respect_gitignore = SettingSource::Cli;
+        }
+        if self.traverse_git_repos {
+            config.traverse_git_repos = true;
+            config.sources.traverse_git_repos = SettingSource::Cli;
+        }
+        if let Some(line_filter) = &self.line_filter {
+            config.line_filter = Some(line_filter.clone());
+        }
+        if let Some(language) = &self.language {
+            config.language = Some(language.clone());
+        }
+    }
+}
+
+/// The setting that put a rule into the preservation rule set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleOrigin {
+    /// Built in and always on (shebangs, `~keep`).
+    Always,
+    /// `remove_todos = false`.
+    KeepTodos,
+    /// `remove_fixme = false`.
+    KeepFixme,
+    /// `remove_docs = false`.
+    KeepDocs,
+    /// A `preserve_patterns` entry.
+    PreservePattern,
+    /// A `preserve_regex` entry.
+    PreserveRegex,
+    /// `use_default_ignores = true`.
+    DefaultIgnores,
 }
 
 pub struct Processor {
@@ -60,51 +148,35 @@ impl Processor {
         let content =
             std::fs::read_to_string(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
 
-        let language_config = self
-            .registry
-            .detect_language_arc(path)
-            .with_context(|| format!("Unsupported file type: {}", path.display()))?;
-
-        let language_name = if language_config.name.bytes().all(|byte| !byte.is_ascii_uppercase()) {
-            Cow::Borrowed(language_config.name.as_str())
-        } else {
-            Cow::Owned(language_config.name.to_lowercase())
-        };
+        self.process_source_with_config(path, content, config_manager, cli_overrides)
+    }
 
-        let mut resolved_config = config_manager.get_config_for_file_with_language(path, &language_name);
+    /// Process in-memory `content` as if it lived at `path`.
+    ///
+    /// `path` only drives language detection and config resolution and need not
+    /// exist, which is what lets stdin be processed under a virtual file name.
+    pub fn process_source_with_config(
+        &mut self,
+        path: &Path,
+        content: String,
+        config_manager: &ConfigManager,
+        cli_overrides: Option<&ProcessingOptions>,
+    ) -> Result<ProcessedFile> {
+        let (language_config, resolved_config) = self.resolve_config(path, &content, config_manager, cli_overrides)?;
+        let language_name = language_config.name.to_lowercase();
 
-        if let Some(overrides) = cli_overrides {
-            if overrides.remove_doc {
-                resolved_config.remove_docs = true;
-            }
-            // Apply flags one-directionally so an unset flag never clobbers config-file
-            // values: only --no-default-ignores forces this off (see issue #106).
-            if !overrides.use_default_ignores {
-                resolved_config.use_default_ignores = false;
-            }
-            if overrides.remove_todo {
-                resolved_config.remove_todos = true;
-            }
-            if overrides.remove_fixme {
-                resolved_config.remove_fixme = true;
+        let outcome = if resolved_config.ignore_file {
+            ProcessOutcome {
+                content: content.clone(),
+                ..ProcessOutcome::default()
             }
-            if !overrides.custom_preserve_patterns.is_empty() {
-                resolved_config
-                    .preserve_patterns
-                    .extend(overrides.custom_preserve_patterns.iter().cloned());
-            }
-            if !overrides.respect_gitignore {
-                resolved_config.respect_gitignore = false;
-            }
-            if overrides.traverse_git_repos {
-                resolved_config.traverse_git_repos = true;
-            }
-        }
-
-        let outcome = self.process_content_with_config(&content, language_config.as_ref(), &resolved_config)?;
+        } else {
+            self.process_content_with_config(&content, language_config.as_ref(), &resolved_config)?
+        };
 
         Ok(ProcessedFile {
             path: path.to_path_buf(),
+            language: language_name,
             original_content: content,
             processed_content: outcome.content,
             modified: false,
@@ -115,12 +187,66 @@ impl Processor {
         })
     }
 
-    fn process_content_with_config(
-        &mut self,
+    /// Pick `path`'s language and resolve its effective config: config files
+    /// first, then the file's own `uncomment:` directives, then any CLI
+    /// overrides layered on top.
+    ///
+    /// The language comes from `--language`, else a matching `[patterns]` entry,
+    /// else detection from the file name, else a shebang or modeline in `content`.
+    pub fn resolve_config(
+        &self,
+        path: &Path,
         content: &str,
-        language_config: &crate::languages::config::LanguageConfig,
-        resolved_config: &ResolvedConfig,
-    ) -> Result<ProcessOutcome> {
+        config_manager: &ConfigManager,
+        cli_overrides: Option<&ProcessingOptions>,
+    ) -> Result<(Arc<LanguageConfig>, ResolvedConfig)> {
+        let forced_language = match cli_overrides.and_then(|overrides| overrides.language.as_ref()) {
+            Some(language) => Some(language.clone()),
+            None => config_manager.language_for_path(path),
+        };
+        let language_config = self.select_language(path, content, forced_language.as_deref())?;
+
+        let language_name = if language_config.name.bytes().all(|byte| !byte.is_ascii_uppercase()) {
+            Cow::Borrowed(language_config.name.as_str())
+        } else {
+            Cow::Owned(language_config.name.to_lowercase())
+        };
+
+        let mut resolved_config = config_manager.get_config_for_file_with_language(path, &language_name);
+        resolved_config.language = forced_language;
+        let directives = FileDirectives::parse(content);
+        for warning in &directives.warnings {
+            eprintln!(
+                "Warning: Ignoring invalid uncomment directive in {}: {warning}",
+                path.display()
+            );
+        }
+        directives.apply_to(&mut resolved_config, path);
+        if let Some(overrides) = cli_overrides {
+            overrides.apply_to(&mut resolved_config);
+        }
+
+        Ok((language_config, resolved_config))
+    }
+
+    /// The language to parse `path` as: `forced` when given, else detected from
+    /// its name or, failing that, from the start of its `content`.
+    fn select_language(&self, path: &Path, content: &str, forced: Option<&str>) -> Result<Arc<LanguageConfig>> {
+        match forced {
+            Some(name) => self
+                .registry
+                .find_language_arc(name)
+                .with_context(|| format!("Unknown language '{name}' for {}", path.display())),
+            None => self
+                .registry
+                .detect_language_arc(path)
+                .or_else(|| self.registry.detect_language_from_content(content))
+                .with_context(|| format!("Unsupported file type: {}", path.display())),
+        }
+    }
+
+    /// Parse `content` with `language_config`'s grammar.
+    fn parse(&mut self, content: &str, language_config: &LanguageConfig) -> Result<Tree> {
         let language = tree_sitter_language_pack::get_language(&language_config.tslp_name).with_context(|| {
             format!(
                 "Failed to load grammar for '{}' (tslp name: '{}')",
@@ -132,24 +258,181 @@ impl Processor {
             .set_language(&language)
             .context("Failed to set parser language")?;
 
-        let tree = self
-            .parser
-            .parse(content, None)
-            .context("Failed to parse source code")?;
+        self.parser.parse(content, None).context("Failed to parse source code")
+    }
 
-        let preservation_rules = self.create_preservation_rules_from_config(resolved_config);
+    /// Every comment in `content`, kept or not, with what decided its fate;
+    /// the basis of `uncomment explain`. Config is resolved exactly as
+    /// [`process_source_with_config`](Self::process_source_with_config) does.
+    pub fn explain(
+        &mut self,
+        path: &Path,
+        content: String,
+        config_manager: &ConfigManager,
+        cli_overrides: Option<&ProcessingOptions>,
+    ) -> Result<Explanation> {
+        let (language_config, config) = self.resolve_config(path, &content, config_manager, cli_overrides)?;
+        let collected = self.collect_comments(&content, &language_config, &config, true)?;
 
-        let mut visitor = CommentVisitor::new_with_language(
+        Ok(Explanation {
+            path: path.to_path_buf(),
+            language: language_config.name.to_lowercase(),
+            handler: collected.handler,
             content,
-            &preservation_rules,
+            comments: collected.comments,
+            kinds: collected.kinds,
+            rules: collected.rules,
+            rule_origins: collected.rule_origins,
+            removal_rules: collected.removal_rules,
+            config,
+        })
+    }
+
+    /// The syntax tree of `content` as the language chosen for `path` parses it,
+    /// flattened in pre-order and with comment nodes marked the way the visitor
+    /// sees them; the basis of `uncomment inspect`.
+    pub fn inspect(&mut self, path: &Path, content: &str, config_manager: &ConfigManager) -> Result<Inspection> {
+        let (language_config, _) = self.resolve_config(path, content, config_manager, None)?;
+        let tree = self.parse(content, &language_config)?;
+        let visitor = CommentVisitor::new_with_language(
+            content,
+            &[],
             &language_config.comment_types,
             &language_config.doc_comment_types,
             &language_config.name,
         );
-        visitor.visit_node(tree.root_node());
-        visitor.extend_keep_blocks();
 
-        let comments_to_remove = visitor.get_comments_to_remove();
+        let mut nodes = Vec::new();
+        let mut cursor = tree.walk();
+        let mut parents: Vec<tree_sitter::Node> = Vec::new();
+        loop {
+            let node = cursor.node();
+            nodes.push(InspectedNode {
+                depth: parents.len(),
+                kind: node.kind(),
+                named: node.is_named(),
+                start_byte: node.start_byte(),
+                end_byte: node.end_byte(),
+                start: (node.start_position().row, node.start_position().column),
+                end: (node.end_position().row, node.end_position().column),
+                comment: visitor
+                    .classify_node(&node, parents.last().copied())
+                    .map(|is_doc| if is_doc { NodeComment::Doc } else { NodeComment::Comment }),
+            });
+
+            if cursor.goto_first_child() {
+                parents.push(node);
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return Ok(Inspection {
+                        language: language_config.name.to_lowercase(),
+                        tslp_name: language_config.tslp_name.clone(),
+                        comment_types: language_config.comment_types.clone(),
+                        doc_comment_types: language_config.doc_comment_types.clone(),
+                        nodes,
+                    });
+                }
+                parents.pop();
+            }
+        }
+    }
+
+    /// Every comment of `content` with whether a run with the same settings would
+    /// keep it; the basis of `--list-comments`.
+    pub fn list_comments(
+        &mut self,
+        path: &Path,
+        content: &str,
+        config_manager: &ConfigManager,
+        cli_overrides: Option<&ProcessingOptions>,
+    ) -> Result<Vec<CommentRecord>> {
+        let (language_config, config) = self.resolve_config(path, content, config_manager, cli_overrides)?;
+        let collected = self.collect_comments(content, &language_config, &config, false)?;
+
+        let language = language_config.name.to_lowercase();
+        Ok(collected
+            .comments
+            .iter()
+            .map(|comment| CommentRecord {
+                path: path.to_path_buf(),
+                language: language.clone(),
+                start_byte: comment.start_byte,
+                end_byte: comment.end_byte,
+                start_row: comment.start_row,
+                end_row: comment.end_row,
+                node_type: comment.node_type,
+                is_documentation: comment.is_documentation,
+                preserved: !collected.is_removed(comment),
+                text: comment.content(content).to_string(),
+            })
+            .collect())
+    }
+
+    /// Comment counts for `content` and what a run with the same settings would
+    /// keep, by rule category; the basis of `uncomment stats`.
+    pub fn comment_stats(
+        &mut self,
+        path: &Path,
+        content: &str,
+        config_manager: &ConfigManager,
+        cli_overrides: Option<&ProcessingOptions>,
+    ) -> Result<FileStats> {
+        let (language_config, config) = self.resolve_config(path, content, config_manager, cli_overrides)?;
+        let collected = self.collect_comments(content, &language_config, &config, false)?;
+
+        let (comment_lines, code_lines) = count_lines(
+            content,
+            collected
+                .comments
+                .iter()
+                .map(|comment| (comment.start_byte, comment.end_byte)),
+        );
+        let mut stats = CommentStats {
+            files: 1,
+            comment_lines,
+            code_lines,
+            ..CommentStats::default()
+        };
+        for comment in &collected.comments {
+            stats.comments += 1;
+            stats.documentation += usize::from(comment.is_documentation);
+            if collected.is_removed(comment) {
+                stats.removed += 1;
+                continue;
+            }
+            stats.preserved += 1;
+            let kept_by = match comment.preserved_by {
+                _ if config.ignore_file => KeptBy::IgnoredFile,
+                Some(Preservation::Handler) => KeptBy::LanguageHandler,
+                Some(
+                    Preservation::KeepBlock
+                    | Preservation::Region
+                    | Preservation::KeepNext
+                    | Preservation::FileDirective,
+                ) => KeptBy::BuiltIn,
+                Some(Preservation::Rule(index)) => KeptBy::from(collected.rule_origins[index]),
+                None => KeptBy::Prose,
+            };
+            *stats.preserved_by.entry(kept_by).or_default() += 1;
+        }
+
+        Ok(FileStats {
+            path: path.to_path_buf(),
+            language: language_config.name.to_lowercase(),
+            stats,
+        })
+    }
+
+    fn process_content_with_config(
+        &mut self,
+        content: &str,
+        language_config: &LanguageConfig,
+        resolved_config: &ResolvedConfig,
+    ) -> Result<ProcessOutcome> {
+        let collected = self.collect_comments(content, language_config, resolved_config, false)?;
+        let comments_to_remove = collected.removed_comments();
 
         let removed_comments = comments_to_remove
             .iter()
@@ -173,30 +456,125 @@ impl Processor {
         })
     }
 
-    fn create_preservation_rules_from_config(&self, config: &ResolvedConfig) -> Vec<PreservationRule> {
+    /// Find every comment of `content` and decide which a run with `config`
+    /// removes: parse, visit with the preservation and removal rules, extend
+    /// `~keep` blocks, apply the `uncomment:` directives, then narrow to the
+    /// line filter and `only_commented_code`. Every entry point that needs to
+    /// know a comment's fate builds on this, so they cannot drift apart.
+    ///
+    /// With `classify`, each comment is also judged code or prose even when
+    /// `only_commented_code` does not call for it.
+    fn collect_comments(
+        &mut self,
+        content: &str,
+        language_config: &LanguageConfig,
+        config: &ResolvedConfig,
+        classify: bool,
+    ) -> Result<CollectedComments> {
+        let tree = self.parse(content, language_config)?;
+        let (rules, rule_origins): (Vec<PreservationRule>, Vec<RuleOrigin>) =
+            self.preservation_rule_set(config).into_iter().unzip();
+        let removal_rules = self.removal_rules(config);
+
+        let mut visitor = CommentVisitor::new_with_language(
+            content,
+            &rules,
+            &language_config.comment_types,
+            &language_config.doc_comment_types,
+            &language_config.name,
+        )
+        .with_removal_rules(&removal_rules);
+        visitor.visit_node(tree.root_node());
+        visitor.extend_keep_blocks();
+        visitor.apply_directives();
+
+        let kinds = if classify || config.only_commented_code {
+            classify_comments(
+                &mut self.parser,
+                content,
+                visitor.comments(),
+                &visitor.line_comment_runs(),
+            )
+        } else {
+            Vec::new()
+        };
+        let mut removed: HashSet<usize> = HashSet::new();
+        if !config.ignore_file {
+            let code: HashSet<usize> = visitor
+                .comments()
+                .iter()
+                .zip(&kinds)
+                .filter(|(_, kind)| **kind == CommentKind::Code)
+                .map(|(comment, _)| comment.start_byte)
+                .collect();
+            removed.extend(
+                visitor
+                    .get_comments_to_remove()
+                    .into_iter()
+                    .filter(|comment| !config.only_commented_code || code.contains(&comment.start_byte))
+                    .filter(|comment| {
+                        config.line_filter.as_ref().is_none_or(|filter| {
+                            let (first, last) = comment_lines(comment, content);
+                            filter.allows(first, last)
+                        })
+                    })
+                    .map(|comment| comment.start_byte),
+            );
+        }
+        let handler = visitor.handler_name();
+        let comments = visitor.comments().to_vec();
+
+        Ok(CollectedComments {
+            comments,
+            removed,
+            kinds,
+            rules,
+            rule_origins,
+            removal_rules,
+            handler,
+        })
+    }
+
+    /// The preservation rules `config` calls for, in the order they are checked.
+    pub fn create_preservation_rules_from_config(&self, config: &ResolvedConfig) -> Vec<PreservationRule> {
+        self.preservation_rule_set(config)
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+
+    /// Like [`create_preservation_rules_from_config`](Self::create_preservation_rules_from_config),
+    /// with the setting that put each rule in the set.
+    pub fn preservation_rule_set(&self, config: &ResolvedConfig) -> Vec<(PreservationRule, RuleOrigin)> {
         let mut rules = Vec::new();
 
-        rules.push(PreservationRule::shebang());
+        rules.push((PreservationRule::shebang(), RuleOrigin::Always));
 
         // Always preserve ~keep
-        rules.push(PreservationRule::pattern("~keep"));
+        rules.push((PreservationRule::pattern("~keep"), RuleOrigin::Always));
 
         // Preserve TODO/FIXME unless explicitly removed
         if !config.remove_todos {
-            rules.push(PreservationRule::pattern("TODO"));
-            rules.push(PreservationRule::pattern("todo"));
+            rules.push((PreservationRule::pattern("TODO"), RuleOrigin::KeepTodos));
+            rules.push((PreservationRule::pattern("todo"), RuleOrigin::KeepTodos));
         }
         if !config.remove_fixme {
-            rules.push(PreservationRule::pattern("FIXME"));
-            rules.push(PreservationRule::pattern("fixme"));
+            rules.push((PreservationRule::pattern("FIXME"), RuleOrigin::KeepFixme));
+            rules.push((PreservationRule::pattern("fixme"), RuleOrigin::KeepFixme));
         }
 
         if !config.remove_docs {
-            rules.push(PreservationRule::documentation());
+            rules.push((PreservationRule::documentation(), RuleOrigin::KeepDocs));
         }
 
         for pattern in &config.preserve_patterns {
-            rules.push(PreservationRule::pattern_owned(pattern.clone()));
+            rules.push((
+                PreservationRule::pattern_owned(pattern.clone()),
+                RuleOrigin::PreservePattern,
+            ));
+        }
+        for regex in &config.preserve_regex {
+            rules.push((PreservationRule::regex(regex.clone()), RuleOrigin::PreserveRegex));
         }
 
         if config.use_default_ignores {
@@ -214,12 +592,28 @@ impl Processor {
                 comprehensive_rules.retain(|rule| !matches!(rule, PreservationRule::Documentation));
             }
 
-            rules.extend(comprehensive_rules);
+            rules.extend(
+                comprehensive_rules
+                    .into_iter()
+                    .map(|rule| (rule, RuleOrigin::DefaultIgnores)),
+            );
         }
 
         rules
     }
 
+    /// The `remove_patterns` and `remove_regex` rules of `config`, which
+    /// override the preservation rules for comments without `~keep`.
+    pub fn removal_rules(&self, config: &ResolvedConfig) -> Vec<PreservationRule> {
+        config
+            .remove_patterns
+            .iter()
+            .cloned()
+            .map(PreservationRule::pattern_owned)
+            .chain(config.remove_regex.iter().cloned().map(PreservationRule::regex))
+            .collect()
+    }
+
     /// Rewrite `content` with the given comments removed, returning the new source
     /// and the byte ranges (in the *original* `content`) that were deleted.
     fn remove_comments_from_content(
@@ -308,8 +702,9 @@ impl Processor {
     /// or rewriting the source, returning one [`Removal`] per comment that would be
     /// stripped (with both the comment span and the expanded delete range).
     ///
-    /// The language is chosen from `path`'s extension via the built-in registry.
-    /// This is a pure in-memory planning API intended for host tools (e.g. editors
+    /// The language is `config.language` when set, otherwise chosen from `path`'s
+    /// extension (or a shebang or modeline in `content`) via the built-in
+    /// registry. This is a pure in-memory planning API intended for host tools (e.g. editors
     /// or linters) that build their own diagnostics/edits from the ranges rather
     /// than consuming the already-rewritten string. Config discovery is *not*
     /// performed — the caller supplies a fully [`ResolvedConfig`].
@@ -320,39 +715,13 @@ impl Processor {
     /// `anyhow`) when `path`'s extension maps to no known language, and propagates
     /// grammar-load / parse failures.
     pub fn plan_removals(&mut self, content: &str, path: &Path, config: &ResolvedConfig) -> Result<Vec<Removal>> {
-        let language_config = self
-            .registry
-            .detect_language_arc(path)
-            .with_context(|| format!("Unsupported file type: {}", path.display()))?;
+        let language_config = self.select_language(path, content, config.language.as_deref())?;
 
-        let language = tree_sitter_language_pack::get_language(&language_config.tslp_name).with_context(|| {
-            format!(
-                "Failed to load grammar for '{}' (tslp name: '{}')",
-                language_config.name, language_config.tslp_name
-            )
-        })?;
-        self.parser
-            .set_language(&language)
-            .context("Failed to set parser language")?;
-        let tree = self
-            .parser
-            .parse(content, None)
-            .context("Failed to parse source code")?;
-
-        let preservation_rules = self.create_preservation_rules_from_config(config);
-        let mut visitor = CommentVisitor::new_with_language(
-            content,
-            &preservation_rules,
-            &language_config.comment_types,
-            &language_config.doc_comment_types,
-            &language_config.name,
-        );
-        visitor.visit_node(tree.root_node());
-        visitor.extend_keep_blocks();
+        let collected = self.collect_comments(content, &language_config, config, false)?;
 
         let bytes = content.as_bytes();
-        let removals = visitor
-            .get_comments_to_remove()
+        let removals = collected
+            .removed_comments()
             .into_iter()
             .filter_map(|comment| {
                 let (remove_start, remove_end) = Self::expand_range(bytes, comment.start_byte, comment.end_byte)?;
@@ -399,6 +768,7 @@ pub struct Removal {
 const PREVIEW_MAX_CHARS: usize = 80;
 
 /// Internal result of rewriting one source string.
+#[derive(Default)]
 struct ProcessOutcome {
     content: String,
     removed_comments: Vec<RemovedComment>,
@@ -409,6 +779,8 @@ struct ProcessOutcome {
 #[derive(Debug)]
 pub struct ProcessedFile {
     pub path: std::path::PathBuf,
+    /// Lowercased name of the language the file was parsed as.
+    pub language: String,
     pub original_content: String,
     pub processed_content: String,
     pub modified: bool,
@@ -421,7 +793,7 @@ pub struct ProcessedFile {
 }
 
 /// A single removed comment, expressed by line for human-facing location output.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RemovedComment {
     /// 0-based first line of the comment.
     pub start_row: usize,
@@ -433,13 +805,60 @@ pub struct RemovedComment {
     pub preview: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ImportantRemoval {
     pub line: usize,
     pub reason: Cow<'static, str>,
     pub preview: String,
 }
 
+/// One file's comments as [`Processor::collect_comments`] found and judged them.
+struct CollectedComments {
+    /// Every comment found, in visiting order.
+    comments: Vec<CommentInfo>,
+    /// Start bytes of the comments a run removes: none that a rule keeps,
+    /// outside the line filter or, under `only_commented_code`, prose; none at
+    /// all in a file with an `uncomment: ignore-file` directive.
+    removed: HashSet<usize>,
+    /// Whether each of `comments` is code or prose; empty unless classification
+    /// was asked for or `only_commented_code` needed it.
+    kinds: Vec<CommentKind>,
+    /// The preservation rules, indexed by [`Preservation::Rule`].
+    rules: Vec<PreservationRule>,
+    /// The setting that put each of `rules` in the set.
+    rule_origins: Vec<RuleOrigin>,
+    removal_rules: Vec<PreservationRule>,
+    /// Name of the language-specific handler, or `None` for the default one.
+    handler: Option<&'static str>,
+}
+
+impl CollectedComments {
+    fn is_removed(&self, comment: &CommentInfo) -> bool {
+        self.removed.contains(&comment.start_byte)
+    }
+
+    /// The comments a run removes, in visiting order.
+    fn removed_comments(&self) -> Vec<&CommentInfo> {
+        self.comments
+            .iter()
+            .filter(|comment| self.is_removed(comment))
+            .collect()
+    }
+}
+
+/// 1-based first and last line a comment occupies. Grammars whose line comments
+/// include the newline end them at column 0 of the next row, which is not a line
+/// the comment is on.
+pub(crate) fn comment_lines(comment: &CommentInfo, content: &str) -> (usize, usize) {
+    let ends_with_newline = content.as_bytes()[..comment.end_byte].last() == Some(&b'\n');
+    let last_row = if ends_with_newline && comment.end_row > comment.start_row {
+        comment.end_row - 1
+    } else {
+        comment.end_row
+    };
+    (comment.start_row + 1, last_row + 1)
+}
+
 /// Trimmed, length-capped first line of a comment, for human-facing messages.
 fn first_line_preview(content: &str) -> String {
     content
@@ -452,11 +871,27 @@ fn first_line_preview(content: &str) -> String {
         .collect()
 }
 
+/// Where [`OutputWriter::write_file`] sends rewritten sources.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WriteTarget {
+    /// Rewrite each file in place.
+    #[default]
+    Files,
+    /// Stream the rewritten source to stdout and move all reporting to stderr.
+    Stdout,
+    /// Stage the rewritten source in the git index of the repository at `root`
+    /// and carry the change over to the working tree.
+    GitIndex { root: std::path::PathBuf },
+}
+
 pub struct OutputWriter {
     dry_run: bool,
     verbose: bool,
     show_diff: bool,
     quiet: bool,
+    target: WriteTarget,
+    journal: Option<crate::journal::Journal>,
+    write_options: crate::atomic_write::WriteOptions,
 }
 
 impl OutputWriter {
@@ -466,10 +901,44 @@ impl OutputWriter {
             verbose,
             show_diff,
             quiet,
+            target: WriteTarget::default(),
+            journal: None,
+            write_options: crate::atomic_write::WriteOptions::default(),
         }
     }
 
-    /// Persist the rewritten file (on a real run) and report what changed.
+    pub fn with_target(mut self, target: WriteTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Journal each file's original before it is overwritten, for `uncomment undo`.
+    pub fn with_journal(mut self, journal: crate::journal::Journal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn with_write_options(mut self, write_options: crate::atomic_write::WriteOptions) -> Self {
+        self.write_options = write_options;
+        self
+    }
+
+    pub fn journal(&self) -> Option<&crate::journal::Journal> {
+        self.journal.as_ref()
+    }
+
+    fn report_stream(&self) -> crate::ui::ReportStream {
+        match self.target {
+            WriteTarget::Files | WriteTarget::GitIndex { .. } => crate::ui::ReportStream::Stdout,
+            WriteTarget::Stdout => crate::ui::ReportStream::Stderr,
+        }
+    }
+
+    fn report(&self, line: std::fmt::Arguments<'_>) {
+        crate::ui::report_line(self.report_stream(), line);
+    }
+
+    /// Persist the rewritten file (on a real run) to the write target and report what changed.
     ///
     /// The write happens before the `quiet` gate so `--quiet` silences reporting
     /// without ever suppressing the actual edit.
@@ -478,9 +947,48 @@ impl OutputWriter {
 
         let modified = processed_file.original_content != processed_file.processed_content;
 
-        if modified && !self.dry_run {
-            std::fs::write(&processed_file.path, &processed_file.processed_content)
+        match &self.target {
+            WriteTarget::Files
+                if modified
+                    && !self.dry_run
+                    && !self.write_options.follow_symlinks
+                    && crate::atomic_write::is_symlink(&processed_file.path) =>
+            {
+                anstream::eprintln!(
+                    "{} {} is a symlink; not modified (--no-follow-symlinks)",
+                    ui::warn("skipped:"),
+                    ui::path(&processed_file.path)
+                );
+                return Ok(());
+            }
+            WriteTarget::Files if modified && !self.dry_run => {
+                if let Some(journal) = &self.journal {
+                    journal.record(
+                        &processed_file.path,
+                        &processed_file.original_content,
+                        &processed_file.processed_content,
+                    )?;
+                }
+                crate::atomic_write::write_atomic(
+                    &processed_file.path,
+                    processed_file.processed_content.as_bytes(),
+                    self.write_options,
+                )
                 .with_context(|| format!("Failed to write file: {}", processed_file.path.display()))?;
+            }
+            // The rewritten source is the output, so it is emitted even when unchanged.
+            WriteTarget::Stdout if !self.dry_run => {
+                use std::io::Write;
+                let mut stdout = std::io::stdout().lock();
+                stdout
+                    .write_all(processed_file.processed_content.as_bytes())
+                    .and_then(|()| stdout.flush())
+                    .context("Failed to write to stdout")?;
+            }
+            WriteTarget::GitIndex { root } if modified && !self.dry_run => {
+                self.write_staged(root, processed_file)?;
+            }
+            _ => {}
         }
 
         if self.quiet {
@@ -489,12 +997,12 @@ impl OutputWriter {
 
         if !modified {
             if self.verbose {
-                anstream::println!(
+                self.report(format_args!(
                     "{} {} {}",
                     ui::success(ui::CHECK),
                     ui::dim("No changes needed:"),
                     ui::path(&processed_file.path)
-                );
+                ));
             }
             return Ok(());
         }
@@ -509,29 +1017,29 @@ impl OutputWriter {
         );
 
         if self.dry_run {
-            anstream::println!(
+            self.report(format_args!(
                 "{} {} {} {}",
                 ui::accent("[DRY RUN]"),
                 ui::dim("Would modify:"),
                 ui::path(&processed_file.path),
                 ui::dim(format!("— would remove {count} ({ranges})")),
-            );
+            ));
         } else {
-            anstream::println!(
+            self.report(format_args!(
                 "{} {} {}",
                 ui::success("Modified:"),
                 ui::path(&processed_file.path),
                 ui::dim(format!("— removed {count} ({ranges})")),
-            );
+            ));
         }
 
         if self.verbose {
             for comment in &processed_file.removed_comments {
-                anstream::println!(
+                self.report(format_args!(
                     "  {}  {}",
                     ui::accent(ui::line_span(comment.start_row, comment.end_row)),
                     ui::dim(&comment.preview),
-                );
+                ));
             }
         }
 
@@ -542,6 +1050,48 @@ impl OutputWriter {
         Ok(())
     }
 
+    /// Stage the rewritten blob, then bring the working tree along: overwrite it
+    /// when it still matches the staged original, otherwise apply the change as a
+    /// patch so unstaged edits in a partially staged file survive.
+    fn write_staged(&self, root: &Path, processed_file: &ProcessedFile) -> Result<()> {
+        use crate::ui;
+
+        let absolute = std::path::absolute(&processed_file.path)
+            .with_context(|| format!("Failed to resolve {}", processed_file.path.display()))?;
+        let relative = absolute
+            .strip_prefix(root)
+            .with_context(|| format!("{} is outside the repository", processed_file.path.display()))?;
+
+        crate::git::stage_content(root, relative, &processed_file.processed_content)
+            .with_context(|| format!("Failed to stage {}", processed_file.path.display()))?;
+
+        let worktree_path = root.join(relative);
+        match std::fs::read_to_string(&worktree_path) {
+            Ok(current) if current == processed_file.original_content => {
+                crate::atomic_write::write_atomic(
+                    &worktree_path,
+                    processed_file.processed_content.as_bytes(),
+                    self.write_options,
+                )
+                .with_context(|| format!("Failed to write file: {}", worktree_path.display()))?;
+            }
+            Ok(_) => {
+                let patch_path = relative.to_string_lossy().replace('\\', "/");
+                let patch = crate::patch::unified_diff_at(processed_file, &patch_path);
+                if crate::git::apply_to_worktree(root, &patch).is_err() {
+                    anstream::eprintln!(
+                        "{} staged {}, but its unstaged edits conflict with the change; working tree left as is",
+                        ui::warn("warning:"),
+                        ui::path(&processed_file.path)
+                    );
+                }
+            }
+            // Deleted from the working tree: only the index copy exists to rewrite.
+            Err(_) => {}
+        }
+        Ok(())
+    }
+
     /// Render a unified-style diff of the removed comments.
     ///
     /// Because `uncomment` only ever deletes, each original line's post-state is
@@ -588,12 +1138,15 @@ impl OutputWriter {
             }
         }
 
-        anstream::println!();
-        anstream::println!("{}", ui::dim(format!("--- {}", processed_file.path.display())));
-        anstream::println!(
+        self.report(format_args!(""));
+        self.report(format_args!(
+            "{}",
+            ui::dim(format!("--- {}", processed_file.path.display()))
+        ));
+        self.report(format_args!(
             "{}",
             ui::dim(format!("+++ {} (processed)", processed_file.path.display()))
-        );
+        ));
 
         let width = format!("{total}").len();
         let mut printed_any = false;
@@ -602,28 +1155,50 @@ impl OutputWriter {
                 continue;
             }
             if printed_any && index > 0 && !show[index - 1] {
-                anstream::println!("{}", ui::dim("  ⋯"));
+                self.report(format_args!("{}", ui::dim("  ⋯")));
             }
             printed_any = true;
 
             let number = format!("{:>width$}", index + 1, width = width);
             match &record.kind {
                 DiffKind::Context => {
-                    anstream::println!("{} {}", ui::dim(&number), ui::dim(format!(" {}", record.text)));
+                    self.report(format_args!(
+                        "{} {}",
+                        ui::dim(&number),
+                        ui::dim(format!(" {}", record.text))
+                    ));
                 }
                 DiffKind::Removed => {
-                    anstream::println!("{} {}", ui::dim(&number), ui::danger(format!("-{}", record.text)));
+                    self.report(format_args!(
+                        "{} {}",
+                        ui::dim(&number),
+                        ui::danger(format!("-{}", record.text))
+                    ));
                 }
                 DiffKind::Changed { remaining } => {
-                    anstream::println!("{} {}", ui::dim(&number), ui::danger(format!("-{}", record.text)));
-                    anstream::println!("{} {}", ui::dim(&number), ui::success(format!("+{remaining}")));
+                    self.report(format_args!(
+                        "{} {}",
+                        ui::dim(&number),
+                        ui::danger(format!("-{}", record.text))
+                    ));
+                    self.report(format_args!(
+                        "{} {}",
+                        ui::dim(&number),
+                        ui::success(format!("+{remaining}"))
+                    ));
                 }
             }
         }
     }
 
     pub fn print_summary(&self, total_files: usize, modified_files: usize, comments_removed: usize) {
-        crate::ui::print_summary(total_files, modified_files, comments_removed, self.dry_run);
+        crate::ui::print_summary(
+            self.report_stream(),
+            total_files,
+            modified_files,
+            comments_removed,
+            self.dry_run,
+        );
     }
 }
 
@@ -643,7 +1218,7 @@ enum DiffKind {
 }
 
 /// Merge sorted/unsorted, possibly overlapping byte ranges into disjoint ranges.
-fn merge_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+pub(crate) fn merge_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
     let mut sorted = ranges.to_vec();
     sorted.sort_unstable();
     let mut merged: Vec<(usize, usize)> = Vec::with_capacity(sorted.len());
@@ -657,7 +1232,7 @@ fn merge_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
 }
 
 /// Return `content[from..to]` with any bytes covered by `merged` ranges removed.
-fn cut_ranges(content: &str, from: usize, to: usize, merged: &[(usize, usize)]) -> String {
+pub(crate) fn cut_ranges(content: &str, from: usize, to: usize, merged: &[(usize, usize)]) -> String {
     let mut out = String::new();
     let mut cursor = from;
     for &(start, end) in merged {
@@ -754,10 +1329,18 @@ mod tests {
             remove_fixme: false,
             remove_docs: false,
             preserve_patterns: Vec::new(),
+            remove_patterns: Vec::new(),
+            preserve_regex: Vec::new(),
+            remove_regex: Vec::new(),
             use_default_ignores: true,
+            only_commented_code: false,
             respect_gitignore: true,
             traverse_git_repos: false,
+            ignore_file: false,
             language_config: None,
+            language: None,
+            line_filter: None,
+            sources: Default::default(),
         }
     }
 
@@ -789,6 +1372,24 @@ mod tests {
         assert_eq!(&source[removals[1].remove_start..removals[1].remove_end], "// trailing");
     }
 
+    #[test]
+    fn line_filter_ignores_the_newline_a_line_comment_ends_with() {
+        use crate::line_filter::{LineFilter, LineFilterMode};
+
+        let source = "// above\nfn f() {}\n// below\n";
+        let mut processor = Processor::new();
+        let config = ResolvedConfig {
+            line_filter: Some(LineFilter::new([2..=3], LineFilterMode::Intersect)),
+            ..default_resolved_config()
+        };
+        let removals = processor
+            .plan_removals(source, std::path::Path::new("sample.rs"), &config)
+            .expect("plan removals");
+
+        let previews: Vec<&str> = removals.iter().map(|removal| removal.preview.as_str()).collect();
+        assert_eq!(previews, vec!["// below"]);
+    }
+
     #[test]
     fn keep_marker_preserves_whole_contiguous_line_comment_block() {
         let source = "fn f() {\n    // line one\n    // line two\n    // line three ~keep\n    let x = 1;\n}\n";
@@ -1053,10 +1654,13 @@ fn main() {}
             remove_doc: true,
             custom_preserve_patterns: Vec::new(),
             use_default_ignores: true,
+            only_commented_code: false,
             dry_run: true,
             show_diff: false,
             respect_gitignore: true,
             traverse_git_repos: false,
+            line_filter: None,
+            language: None,
         };
 
         let with_defaults = processor
@@ -1098,10 +1702,13 @@ fn main() {}
             remove_doc: false,
             custom_preserve_patterns: Vec::new(),
             use_default_ignores: true,
+            only_commented_code: false,
             dry_run: true,
             show_diff: false,
             respect_gitignore: true,
             traverse_git_repos: false,
+            line_filter: None,
+            language: None,
         };
 
         let mut processor = Processor::new();
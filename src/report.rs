@@ -0,0 +1,182 @@
+//! Machine-readable run reports (`--output-format json|ndjson`).
+//!
+//! The records borrow from [`ProcessedFile`] rather than copying it, and carry
+//! the same totals as [`crate::ui::print_summary`] so dashboards and the human
+//! summary never disagree.
+
+use crate::processor::{ImportantRemoval, ProcessedFile, RemovedComment};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A file that could not be processed, kept so structured reports can list it.
+#[derive(Debug)]
+pub struct ProcessingFailure {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// The outcome of processing one input, in input order.
+pub type FileOutcome = std::result::Result<ProcessedFile, ProcessingFailure>;
+
+/// One per-file record. `error` is set (and the other fields are empty) when
+/// the file could not be processed.
+#[derive(Debug, Serialize)]
+pub struct FileRecord<'a> {
+    pub path: &'a Path,
+    pub language: Option<&'a str>,
+    pub modified: bool,
+    pub comments_removed: usize,
+    pub removed_comments: &'a [RemovedComment],
+    pub important_removals: &'a [ImportantRemoval],
+    pub error: Option<&'a str>,
+}
+
+impl<'a> FileRecord<'a> {
+    pub fn from_outcome(outcome: &'a FileOutcome) -> Self {
+        match outcome {
+            Ok(processed) => Self {
+                path: &processed.path,
+                language: Some(&processed.language),
+                modified: processed.modified,
+                comments_removed: processed.comments_removed,
+                removed_comments: &processed.removed_comments,
+                important_removals: &processed.important_removals,
+                error: None,
+            },
+            Err(failure) => Self {
+                path: &failure.path,
+                language: None,
+                modified: false,
+                comments_removed: 0,
+                removed_comments: &[],
+                important_removals: &[],
+                error: Some(&failure.message),
+            },
+        }
+    }
+}
+
+/// End-of-run totals, mirroring the human `Summary:` line.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub total_files: usize,
+    pub modified_files: usize,
+    pub comments_removed: usize,
+    pub failed_files: usize,
+    pub dry_run: bool,
+    /// Generated files left out of the run; see [`crate::generated`].
+    pub skipped_generated: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    files: Vec<FileRecord<'a>>,
+    summary: &'a Summary,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NdjsonRecord<'a> {
+    File(FileRecord<'a>),
+    Summary(&'a Summary),
+}
+
+/// Write a single JSON document: `{"files": [...], "summary": {...}}`.
+pub fn write_json(out: &mut impl Write, outcomes: &[FileOutcome], summary: &Summary) -> Result<()> {
+    let report = JsonReport {
+        files: outcomes.iter().map(FileRecord::from_outcome).collect(),
+        summary,
+    };
+    serde_json::to_writer_pretty(&mut *out, &report).context("Failed to serialize JSON report")?;
+    writeln!(out).context("Failed to write JSON report")?;
+    Ok(())
+}
+
+/// Write one `{"type": "file", ...}` line per file, then a `{"type": "summary", ...}` line.
+pub fn write_ndjson(out: &mut impl Write, outcomes: &[FileOutcome], summary: &Summary) -> Result<()> {
+    let records = outcomes
+        .iter()
+        .map(|outcome| NdjsonRecord::File(FileRecord::from_outcome(outcome)))
+        .chain(std::iter::once(NdjsonRecord::Summary(summary)));
+
+    for record in records {
+        serde_json::to_writer(&mut *out, &record).context("Failed to serialize NDJSON record")?;
+        writeln!(out).context("Failed to write NDJSON record")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processed(path: &str) -> ProcessedFile {
+        ProcessedFile {
+            path: PathBuf::from(path),
+            language: "rust".to_string(),
+            original_content: "// x\nfn f() {}\n".to_string(),
+            processed_content: "fn f() {}\n".to_string(),
+            modified: true,
+            comments_removed: 1,
+            removed_comments: vec![RemovedComment {
+                start_row: 0,
+                end_row: 0,
+                is_documentation: false,
+                preview: "// x".to_string(),
+            }],
+            removed_ranges: vec![(0, 5)],
+            important_removals: Vec::new(),
+        }
+    }
+
+    fn summary() -> Summary {
+        Summary {
+            total_files: 2,
+            modified_files: 1,
+            comments_removed: 1,
+            failed_files: 1,
+            dry_run: true,
+            skipped_generated: vec![PathBuf::from("api_pb2.py")],
+        }
+    }
+
+    #[test]
+    fn ndjson_emits_tagged_file_records_then_summary() {
+        let outcomes = vec![
+            Ok(processed("a.rs")),
+            Err(ProcessingFailure {
+                path: PathBuf::from("b.rs"),
+                message: "boom".to_string(),
+            }),
+        ];
+        let mut out = Vec::new();
+        write_ndjson(&mut out, &outcomes, &summary()).unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "file");
+        assert_eq!(lines[0]["language"], "rust");
+        assert_eq!(lines[0]["removed_comments"][0]["preview"], "// x");
+        assert_eq!(lines[1]["error"], "boom");
+        assert!(lines[1]["language"].is_null());
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["failed_files"], 1);
+    }
+
+    #[test]
+    fn json_is_a_single_document() {
+        let outcomes = vec![Ok(processed("a.rs"))];
+        let mut out = Vec::new();
+        write_json(&mut out, &outcomes, &summary()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["files"][0]["path"], "a.rs");
+        assert_eq!(value["summary"]["modified_files"], 1);
+    }
+}
@@ -1,18 +1,123 @@
 use crate::ast::visitor::CommentInfo;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum PreservationRule {
     Pattern(Cow<'static, str>),
+    Regex(RegexPattern),
     Documentation,
     FileHeader,
     Shebang,
 }
 
+/// A regular expression from a `preserve_regex` or `remove_regex` list.
+///
+/// In config files an entry is either a bare pattern or a table with options:
+/// `{ pattern = "TODO\\(#\\d+\\)", whole_word = true, case_insensitive = true }`.
+/// The expression is compiled once, when the config is read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RegexPatternSpec", into = "RegexPatternSpec")]
+pub struct RegexPattern {
+    pub pattern: String,
+    /// Only match where the pattern starts and ends at a word boundary.
+    pub whole_word: bool,
+    pub case_insensitive: bool,
+    regex: Regex,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RegexPatternSpec {
+    Plain(String),
+    Options {
+        pattern: String,
+        #[serde(default)]
+        whole_word: bool,
+        #[serde(default)]
+        case_insensitive: bool,
+    },
+}
+
+impl RegexPattern {
+    pub fn new(pattern: &str, whole_word: bool, case_insensitive: bool) -> Result<Self, regex::Error> {
+        let source = if whole_word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern.to_string()
+        };
+        let regex = RegexBuilder::new(&source).case_insensitive(case_insensitive).build()?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            whole_word,
+            case_insensitive,
+            regex,
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+}
+
+impl PartialEq for RegexPattern {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.pattern, self.whole_word, self.case_insensitive)
+            == (&other.pattern, other.whole_word, other.case_insensitive)
+    }
+}
+
+impl Eq for RegexPattern {}
+
+impl fmt::Display for RegexPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}/", self.pattern)?;
+        if self.whole_word {
+            write!(f, " whole word")?;
+        }
+        if self.case_insensitive {
+            write!(f, " case-insensitive")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<RegexPatternSpec> for RegexPattern {
+    type Error = String;
+
+    fn try_from(spec: RegexPatternSpec) -> Result<Self, Self::Error> {
+        let (pattern, whole_word, case_insensitive) = match spec {
+            RegexPatternSpec::Plain(pattern) => (pattern, false, false),
+            RegexPatternSpec::Options {
+                pattern,
+                whole_word,
+                case_insensitive,
+            } => (pattern, whole_word, case_insensitive),
+        };
+        Self::new(&pattern, whole_word, case_insensitive).map_err(|e| format!("invalid regex {pattern:?}: {e}"))
+    }
+}
+
+impl From<RegexPattern> for RegexPatternSpec {
+    fn from(pattern: RegexPattern) -> Self {
+        if !pattern.whole_word && !pattern.case_insensitive {
+            return RegexPatternSpec::Plain(pattern.pattern);
+        }
+        RegexPatternSpec::Options {
+            pattern: pattern.pattern,
+            whole_word: pattern.whole_word,
+            case_insensitive: pattern.case_insensitive,
+        }
+    }
+}
+
 impl PreservationRule {
     pub fn matches(&self, comment: &CommentInfo, content: &str) -> bool {
         match self {
             PreservationRule::Pattern(pattern) => content.contains(pattern.as_ref()),
+            PreservationRule::Regex(pattern) => pattern.is_match(content),
             PreservationRule::Documentation => self.is_documentation_comment(comment, content),
             PreservationRule::FileHeader => self.is_file_header_comment(comment, content),
             PreservationRule::Shebang => self.is_shebang(comment, content),
@@ -113,6 +218,10 @@ impl PreservationRule {
         PreservationRule::Pattern(Cow::Owned(pattern))
     }
 
+    pub fn regex(pattern: RegexPattern) -> Self {
+        PreservationRule::Regex(pattern)
+    }
+
     pub fn documentation() -> Self {
         PreservationRule::Documentation
     }
@@ -126,6 +235,18 @@ impl PreservationRule {
     }
 }
 
+impl fmt::Display for PreservationRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreservationRule::Pattern(pattern) => write!(f, "pattern {pattern:?}"),
+            PreservationRule::Regex(pattern) => write!(f, "regex {pattern}"),
+            PreservationRule::Documentation => write!(f, "documentation"),
+            PreservationRule::FileHeader => write!(f, "file header"),
+            PreservationRule::Shebang => write!(f, "shebang"),
+        }
+    }
+}
+
 impl PreservationRule {
     pub fn default_rules() -> Vec<Self> {
         vec![
@@ -314,6 +435,8 @@ mod tests {
             node_type,
             should_preserve: false,
             is_documentation: false,
+            preserved_by: None,
+            removed_by: None,
         }
     }
 
@@ -325,6 +448,37 @@ mod tests {
         assert!(!rule.matches(&comment, "// Regular comment"));
     }
 
+    #[test]
+    fn test_regex_rule_options() {
+        let comment = create_test_comment("line_comment", 5);
+
+        let issue = PreservationRule::regex(RegexPattern::new(r"TODO\(#\d+\)", false, false).unwrap());
+        assert!(issue.matches(&comment, "// TODO(#42): tidy"));
+        assert!(!issue.matches(&comment, "// TODO: tidy"));
+
+        let word = PreservationRule::regex(RegexPattern::new("todo", true, true).unwrap());
+        assert!(word.matches(&comment, "// Todo: tidy"));
+        assert!(!word.matches(&comment, "// TODOS are bad"));
+        assert!(!word.matches(&comment, "// mastodon TODOlist"));
+    }
+
+    #[test]
+    fn test_regex_pattern_config_forms() {
+        #[derive(Debug, Deserialize)]
+        struct Entries {
+            regex: Vec<RegexPattern>,
+        }
+        let entries: Entries = toml::from_str(
+            r#"regex = ["^// keep", { pattern = "safety", whole_word = true, case_insensitive = true }]"#,
+        )
+        .unwrap();
+        assert_eq!(entries.regex[0], RegexPattern::new("^// keep", false, false).unwrap());
+        assert_eq!(entries.regex[1], RegexPattern::new("safety", true, true).unwrap());
+
+        let invalid = toml::from_str::<Entries>(r#"regex = ["(unclosed"]"#);
+        assert!(invalid.unwrap_err().to_string().contains("invalid regex"));
+    }
+
     #[test]
     fn test_documentation_rule() {
         let rule = PreservationRule::documentation();
@@ -365,6 +519,10 @@ mod tests {
             &autogenerated,
             "/* Auto-generated by cbindgen. Don't modify this file manually. */"
         ));
+
+        // Generated-file markers are generated.rs's concern, not the header rule's.
+        assert!(!rule.matches(&early_comment, "// @generated"));
+        assert!(!rule.matches(&early_comment, "// Code generated from the schema"));
     }
 
     #[test]
@@ -0,0 +1,424 @@
+//! SARIF 2.1.0 output (`--output-format sarif`) for code-scanning tools.
+//!
+//! Every removable comment becomes a result located at the comment token itself,
+//! with a fix that deletes [`Removal::remove_start`]`..`[`Removal::remove_end`] —
+//! the same bytes a real run would cut. Removals that
+//! [`ImportantRemoval`] flags (directives, magic comments, …) are reported under
+//! a separate, higher-severity rule.
+
+use crate::processor::{ImportantRemoval, Removal};
+use crate::report::ProcessingFailure;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const REMOVABLE_RULE: &str = "removable-comment";
+const IMPORTANT_RULE: &str = "important-comment-removal";
+
+/// The removals planned for one file, plus the source they index into.
+pub struct SarifFile<'a> {
+    pub path: &'a Path,
+    pub content: &'a str,
+    pub removals: Vec<Removal>,
+    pub important_removals: &'a [ImportantRemoval],
+}
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool,
+    column_kind: &'static str,
+    invocations: [Invocation<'a>; 1],
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: [Rule; 2],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message<'static>,
+    default_configuration: RuleConfiguration,
+}
+
+#[derive(Serialize)]
+struct RuleConfiguration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: Vec<Notification<'a>>,
+}
+
+#[derive(Serialize)]
+struct Notification<'a> {
+    level: &'static str,
+    message: Message<'a>,
+    locations: [Location; 1],
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    text: std::borrow::Cow<'a, str>,
+}
+
+impl<'a> Message<'a> {
+    fn new(text: impl Into<std::borrow::Cow<'a, str>>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message<'static>,
+    locations: [Location; 1],
+    fixes: [Fix; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Serialize, Clone)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+    byte_offset: usize,
+    byte_length: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message<'static>,
+    artifact_changes: [ArtifactChange; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: [Replacement; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+}
+
+/// Maps byte offsets to SARIF's 1-based line and code-point column numbers.
+struct LineIndex<'a> {
+    content: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(content: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(memchr::memchr_iter(b'\n', content.as_bytes()).map(|index| index + 1))
+            .collect();
+        Self { content, line_starts }
+    }
+
+    fn position(&self, byte: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= byte) - 1;
+        let column = self.content[self.line_starts[line]..byte].chars().count();
+        (line + 1, column + 1)
+    }
+}
+
+/// A relative URI reference for a relative path, or a `file:` URI for an
+/// absolute one: `file:///C:/...` for a drive path, `file://host/...` for a
+/// UNC path.
+fn artifact_uri(path: &Path) -> String {
+    let normalized = path.to_string_lossy().replace('\\', "/");
+    let encoded = percent_encode_path(&normalized);
+    let drive = normalized.as_bytes();
+    if drive.len() >= 2 && drive[0].is_ascii_alphabetic() && drive[1] == b':' {
+        format!("file:///{encoded}")
+    } else if normalized.starts_with("//") {
+        format!("file:{encoded}")
+    } else if path.is_absolute() {
+        format!("file://{encoded}")
+    } else {
+        encoded.trim_start_matches("./").to_string()
+    }
+}
+
+/// Percent-encodes every byte of `path` that may not appear in a URI path
+/// segment, keeping `/` as the separator.
+fn percent_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn file_results(file: &SarifFile<'_>) -> Vec<SarifResult> {
+    let index = LineIndex::new(file.content);
+    let uri = ArtifactLocation {
+        uri: artifact_uri(file.path),
+    };
+
+    file.removals
+        .iter()
+        .map(|removal| {
+            let (start_line, start_column) = index.position(removal.comment_start);
+            let (end_line, end_column) = index.position(removal.comment_end);
+            let important = file
+                .important_removals
+                .iter()
+                .find(|important| important.line == removal.start_row + 1);
+
+            let (rule_id, rule_index, level, message) = match important {
+                Some(important) => (
+                    IMPORTANT_RULE,
+                    1,
+                    "warning",
+                    format!("Removing {}: {}", important.reason, removal.preview),
+                ),
+                None => (
+                    REMOVABLE_RULE,
+                    0,
+                    "note",
+                    format!("Removable comment: {}", removal.preview),
+                ),
+            };
+
+            SarifResult {
+                rule_id,
+                rule_index,
+                level,
+                message: Message::new(message),
+                locations: [Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: uri.clone(),
+                        region: Some(Region {
+                            start_line: Some(start_line),
+                            start_column: Some(start_column),
+                            end_line: Some(end_line),
+                            end_column: Some(end_column),
+                            byte_offset: removal.comment_start,
+                            byte_length: removal.comment_end - removal.comment_start,
+                        }),
+                    },
+                }],
+                fixes: [Fix {
+                    description: Message::new("Remove the comment"),
+                    artifact_changes: [ArtifactChange {
+                        artifact_location: uri.clone(),
+                        replacements: [Replacement {
+                            deleted_region: Region {
+                                byte_offset: removal.remove_start,
+                                byte_length: removal.remove_end - removal.remove_start,
+                                ..Region::default()
+                            },
+                        }],
+                    }],
+                }],
+            }
+        })
+        .collect()
+}
+
+/// Write a single-run SARIF 2.1.0 log. Files that failed to process are listed
+/// as tool execution notifications rather than results.
+pub fn write_sarif(out: &mut impl Write, files: &[SarifFile<'_>], failures: &[&ProcessingFailure]) -> Result<()> {
+    let log = Log {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "uncomment",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules: [
+                        Rule {
+                            id: REMOVABLE_RULE,
+                            short_description: Message::new("Comment that uncomment would remove"),
+                            default_configuration: RuleConfiguration { level: "note" },
+                        },
+                        Rule {
+                            id: IMPORTANT_RULE,
+                            short_description: Message::new(
+                                "Removable comment that looks like a directive or magic comment",
+                            ),
+                            default_configuration: RuleConfiguration { level: "warning" },
+                        },
+                    ],
+                },
+            },
+            column_kind: "unicodeCodePoints",
+            invocations: [Invocation {
+                execution_successful: failures.is_empty(),
+                tool_execution_notifications: failures
+                    .iter()
+                    .map(|failure| Notification {
+                        level: "error",
+                        message: Message::new(failure.message.as_str()),
+                        locations: [Location {
+                            physical_location: PhysicalLocation {
+                                artifact_location: ArtifactLocation {
+                                    uri: artifact_uri(&failure.path),
+                                },
+                                region: None,
+                            },
+                        }],
+                    })
+                    .collect(),
+            }],
+            results: files.iter().flat_map(file_results).collect(),
+        }],
+    };
+
+    serde_json::to_writer_pretty(&mut *out, &log).context("Failed to serialize SARIF log")?;
+    writeln!(out).context("Failed to write SARIF log")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn removal(source: &str, comment: &str, remove: &str) -> Removal {
+        let comment_start = source.find(comment).unwrap();
+        let remove_start = source.find(remove).unwrap();
+        Removal {
+            comment_start,
+            comment_end: comment_start + comment.len(),
+            remove_start,
+            remove_end: remove_start + remove.len(),
+            start_row: source[..comment_start].matches('\n').count(),
+            is_documentation: false,
+            preview: comment.to_string(),
+        }
+    }
+
+    #[test]
+    fn artifact_uris_are_valid_for_every_path_form() {
+        assert_eq!(artifact_uri(Path::new("./src/my file.rs")), "src/my%20file.rs");
+        assert_eq!(
+            artifact_uri(Path::new("/home/me/a b/é.rs")),
+            "file:///home/me/a%20b/%C3%A9.rs"
+        );
+        assert_eq!(
+            artifact_uri(Path::new("C:\\Users\\me\\a.rs")),
+            "file:///C:/Users/me/a.rs"
+        );
+        assert_eq!(
+            artifact_uri(Path::new("\\\\server\\share\\a.rs")),
+            "file://server/share/a.rs"
+        );
+        assert_eq!(artifact_uri(Path::new("src/100%.rs")), "src/100%25.rs");
+    }
+
+    #[test]
+    fn line_index_reports_one_based_code_point_columns() {
+        let index = LineIndex::new("é = 1\nlet x; // c\n");
+        assert_eq!(index.position(0), (1, 1));
+        assert_eq!(index.position("é = 1\nlet x; ".len()), (2, 8));
+        assert_eq!(index.position("é = ".len()), (1, 5));
+    }
+
+    #[test]
+    fn results_carry_locations_fixes_and_rule_levels() {
+        let source = "// plain\nfn f() {} // eslint-disable-line\n";
+        let important = [ImportantRemoval {
+            line: 2,
+            reason: Cow::Borrowed("linter/formatter directive"),
+            preview: "// eslint-disable-line".to_string(),
+        }];
+        let files = [SarifFile {
+            path: Path::new("src/lib.rs"),
+            content: source,
+            removals: vec![
+                removal(source, "// plain", "// plain\n"),
+                removal(source, "// eslint-disable-line", " // eslint-disable-line"),
+            ],
+            important_removals: &important,
+        }];
+
+        let mut out = Vec::new();
+        write_sarif(&mut out, &files, &[]).unwrap();
+        let log: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let results = &log["runs"][0]["results"];
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(results[0]["ruleId"], REMOVABLE_RULE);
+        assert_eq!(results[0]["level"], "note");
+        let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 1);
+        assert_eq!(region["endColumn"], 9);
+        let deleted = &results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0]["deletedRegion"];
+        assert_eq!(deleted["byteOffset"], 0);
+        assert_eq!(deleted["byteLength"], 9);
+
+        assert_eq!(results[1]["ruleId"], IMPORTANT_RULE);
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"]["region"]["startColumn"],
+            11
+        );
+    }
+}
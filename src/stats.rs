@@ -0,0 +1,253 @@
+//! Comment density per language and directory (`uncomment stats`).
+//!
+//! Counts come from the same visitor, rules and config as a normal run, so what
+//! `stats` calls a comment, and whether it would be kept, is exactly what
+//! `uncomment` itself would do to the tree.
+
+use crate::processor::RuleOrigin;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+/// What kept a comment, grouped by the setting a user would reach for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeptBy {
+    /// Shebangs, `~keep` (including the rest of a `~keep` block) and the
+    /// `uncomment:` directives.
+    BuiltIn,
+    Todo,
+    Fixme,
+    Documentation,
+    /// `preserve_patterns` and `preserve_regex`.
+    CustomRule,
+    /// Linting directives and the other default ignores.
+    DefaultIgnores,
+    /// The language handler, e.g. Go's `//go:` directives.
+    LanguageHandler,
+    /// Prose left alone under `only_commented_code`.
+    Prose,
+    /// Anything in a file with an `uncomment: ignore-file` directive.
+    IgnoredFile,
+}
+
+impl KeptBy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeptBy::BuiltIn => "built-in",
+            KeptBy::Todo => "todo",
+            KeptBy::Fixme => "fixme",
+            KeptBy::Documentation => "documentation",
+            KeptBy::CustomRule => "custom rule",
+            KeptBy::DefaultIgnores => "default ignores",
+            KeptBy::LanguageHandler => "language handler",
+            KeptBy::Prose => "prose",
+            KeptBy::IgnoredFile => "ignored file",
+        }
+    }
+}
+
+impl From<RuleOrigin> for KeptBy {
+    fn from(origin: RuleOrigin) -> Self {
+        match origin {
+            RuleOrigin::Always => KeptBy::BuiltIn,
+            RuleOrigin::KeepTodos => KeptBy::Todo,
+            RuleOrigin::KeepFixme => KeptBy::Fixme,
+            RuleOrigin::KeepDocs => KeptBy::Documentation,
+            RuleOrigin::PreservePattern | RuleOrigin::PreserveRegex => KeptBy::CustomRule,
+            RuleOrigin::DefaultIgnores => KeptBy::DefaultIgnores,
+        }
+    }
+}
+
+/// Comment counts for one file or a group of files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommentStats {
+    pub files: usize,
+    pub comments: usize,
+    /// Lines holding any part of a comment.
+    pub comment_lines: usize,
+    /// Lines holding anything other than comments and whitespace. A line with
+    /// code and a trailing comment counts as both.
+    pub code_lines: usize,
+    pub documentation: usize,
+    pub preserved: usize,
+    pub removed: usize,
+    pub preserved_by: BTreeMap<KeptBy, usize>,
+}
+
+impl CommentStats {
+    /// Comment lines per code line, or `None` without code.
+    pub fn comment_ratio(&self) -> Option<f64> {
+        (self.code_lines > 0).then(|| self.comment_lines as f64 / self.code_lines as f64)
+    }
+
+    pub fn add(&mut self, other: &CommentStats) {
+        self.files += other.files;
+        self.comments += other.comments;
+        self.comment_lines += other.comment_lines;
+        self.code_lines += other.code_lines;
+        self.documentation += other.documentation;
+        self.preserved += other.preserved;
+        self.removed += other.removed;
+        for (kept_by, count) in &other.preserved_by {
+            *self.preserved_by.entry(*kept_by).or_default() += count;
+        }
+    }
+}
+
+/// The counts of one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStats {
+    pub path: PathBuf,
+    pub language: String,
+    pub stats: CommentStats,
+}
+
+/// Counts for a whole run, by language and by directory. A directory's counts
+/// cover the files directly in it, not those of its subdirectories.
+#[derive(Debug, Clone, Default)]
+pub struct StatsReport {
+    pub total: CommentStats,
+    pub languages: BTreeMap<String, CommentStats>,
+    pub directories: BTreeMap<PathBuf, CommentStats>,
+}
+
+impl StatsReport {
+    pub fn add(&mut self, file: &FileStats) {
+        let directory: PathBuf = file
+            .path
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter(|component| *component != Component::CurDir)
+            .collect();
+        let directory = if directory.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            directory
+        };
+        self.total.add(&file.stats);
+        self.languages
+            .entry(file.language.clone())
+            .or_default()
+            .add(&file.stats);
+        self.directories.entry(directory).or_default().add(&file.stats);
+    }
+
+    /// Write the report as one JSON document, each group with its
+    /// `comment_to_code_ratio`.
+    pub fn write_json(&self, out: &mut impl Write) -> Result<()> {
+        #[derive(Serialize)]
+        struct Row<'a> {
+            #[serde(flatten)]
+            stats: &'a CommentStats,
+            comment_to_code_ratio: Option<f64>,
+        }
+        #[derive(Serialize)]
+        struct Document<'a> {
+            total: Row<'a>,
+            languages: BTreeMap<&'a str, Row<'a>>,
+            directories: BTreeMap<String, Row<'a>>,
+        }
+
+        let row = |stats| Row {
+            stats,
+            comment_to_code_ratio: stats.comment_ratio(),
+        };
+        let document = Document {
+            total: row(&self.total),
+            languages: self
+                .languages
+                .iter()
+                .map(|(language, stats)| (language.as_str(), row(stats)))
+                .collect(),
+            directories: self
+                .directories
+                .iter()
+                .map(|(directory, stats)| (directory.display().to_string(), row(stats)))
+                .collect(),
+        };
+        serde_json::to_writer_pretty(&mut *out, &document).context("Failed to serialize comment statistics")?;
+        writeln!(out).context("Failed to write comment statistics")?;
+        Ok(())
+    }
+}
+
+/// Comment and code lines of `content`, given the byte ranges of its comments.
+pub fn count_lines(content: &str, comments: impl IntoIterator<Item = (usize, usize)>) -> (usize, usize) {
+    let bytes = content.as_bytes();
+    let mut in_comment = vec![false; bytes.len()];
+    for (start, end) in comments {
+        in_comment[start..end].fill(true);
+    }
+
+    let (mut comment_lines, mut code_lines) = (0, 0);
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let range = offset..offset + line.len();
+        offset = range.end;
+        let visible = range.filter(|&i| !bytes[i].is_ascii_whitespace());
+        let (mut comment, mut code) = (false, false);
+        for i in visible {
+            if in_comment[i] {
+                comment = true;
+            } else {
+                code = true;
+            }
+        }
+        comment_lines += usize::from(comment);
+        code_lines += usize::from(code);
+    }
+    (comment_lines, code_lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_trailing_comments_as_code_and_comment_lines() {
+        let content = "// header\nlet x = 1; // why\n\n/* a\n   b */\nlet y = 2;\n";
+        let spans = [(0, 9), (21, 27), (29, 41)];
+        assert_eq!(count_lines(content, spans), (4, 2));
+    }
+
+    #[test]
+    fn groups_files_by_language_and_directory() {
+        let file = |path: &str, language: &str, comments| FileStats {
+            path: PathBuf::from(path),
+            language: language.to_string(),
+            stats: CommentStats {
+                files: 1,
+                comments,
+                comment_lines: comments,
+                code_lines: 10,
+                preserved: 1,
+                removed: comments - 1,
+                preserved_by: BTreeMap::from([(KeptBy::Todo, 1)]),
+                ..CommentStats::default()
+            },
+        };
+        let mut report = StatsReport::default();
+        report.add(&file("src/a.rs", "rust", 2));
+        report.add(&file("src/b.py", "python", 3));
+        report.add(&file("./main.rs", "rust", 4));
+
+        assert_eq!(report.total.comments, 9);
+        assert_eq!(report.total.preserved_by[&KeptBy::Todo], 3);
+        assert_eq!(report.languages["rust"].files, 2);
+        assert_eq!(report.languages["rust"].comment_ratio(), Some(0.3));
+        assert_eq!(report.directories[Path::new("src")].removed, 3);
+        assert_eq!(report.directories[Path::new(".")].comments, 4);
+
+        let mut out = Vec::new();
+        report.write_json(&mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["languages"]["python"]["comments"], 3);
+        assert_eq!(json["languages"]["python"]["comment_to_code_ratio"], 0.3);
+        assert_eq!(json["directories"]["src"]["preserved_by"]["todo"], 2);
+    }
+}
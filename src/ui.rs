@@ -56,6 +56,25 @@ pub fn bold(value: impl Display) -> String {
     value.bold().to_string()
 }
 
+/// The stream human-facing reports are printed to.
+///
+/// Reports normally go to stdout; when stdout carries data instead (the
+/// rewritten source in stdin mode), they move to stderr so pipes stay clean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// Print one report line to the given stream.
+pub fn report_line(stream: ReportStream, line: std::fmt::Arguments<'_>) {
+    match stream {
+        ReportStream::Stdout => anstream::println!("{line}"),
+        ReportStream::Stderr => anstream::eprintln!("{line}"),
+    }
+}
+
 /// Cap on the number of line spans shown per file before `--verbose`; keeps the
 /// per-file line readable on large removals.
 const LINE_RANGE_CAP: usize = 12;
@@ -123,25 +142,37 @@ pub fn progress_bar(len: u64) -> ProgressBar {
 /// integration tests; only the coloring is cosmetic. Colors are applied around —
 /// never inside — those tokens, and `anstream` strips them for non-terminal
 /// consumers, so the plain-text tokens are always intact.
-pub fn print_summary(total_files: usize, modified_files: usize, comments_removed: usize, dry_run: bool) {
+pub fn print_summary(
+    stream: ReportStream,
+    total_files: usize,
+    modified_files: usize,
+    comments_removed: usize,
+    dry_run: bool,
+) {
     let prefix = if dry_run { "[DRY RUN] " } else { "" };
     let modified_verb = if dry_run { "would be modified" } else { "modified" };
 
-    anstream::println!();
-    anstream::println!(
-        "{}{} {} files processed, {} {}, {} comments removed",
-        dim(prefix),
-        bold("Summary:"),
-        accent(total_files),
-        accent(modified_files),
-        modified_verb,
-        accent(comments_removed),
+    report_line(stream, format_args!(""));
+    report_line(
+        stream,
+        format_args!(
+            "{}{} {} files processed, {} {}, {} comments removed",
+            dim(prefix),
+            bold("Summary:"),
+            accent(total_files),
+            accent(modified_files),
+            modified_verb,
+            accent(comments_removed),
+        ),
     );
 
     if total_files > 0 && modified_files == 0 {
-        anstream::println!(
-            "{}",
-            dim("All files were already comment-free or only contained preserved comments.")
+        report_line(
+            stream,
+            format_args!(
+                "{}",
+                dim("All files were already comment-free or only contained preserved comments.")
+            ),
         );
     }
 }
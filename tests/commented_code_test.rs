@@ -0,0 +1,62 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const SOURCE: &str = "// Compute the total.\n// let old = legacy(a, b);\nfn total() -> i32 {\n    \
+                      // if y > 0 {\n    //     return y;\n    // }\n    1 // returns one\n}\n";
+
+const CODE_REMOVED: &str = "// Compute the total.\nfn total() -> i32 {\n    1 // returns one\n}\n";
+
+#[test]
+fn only_commented_code_flag_keeps_prose() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+    fs::write(dir.path().join("b.py"), "# Note: slow\n# x = compute(a, b)\ny = 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([".", "--only-commented-code"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), CODE_REMOVED);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("b.py")).unwrap(),
+        "# Note: slow\ny = 1\n"
+    );
+}
+
+#[test]
+fn config_flag_applies_and_explain_reports_the_classification() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nonly_commented_code = true\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["explain", "a.rs"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[1].contains("kept") && lines[1].ends_with("(prose)"), "{stdout}");
+    assert!(lines[2].contains("prose; only commented-out code is removed · only_commented_code = true"));
+    assert!(
+        lines[3].contains("removed") && lines[3].ends_with("(commented-out code)"),
+        "{stdout}"
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg(".")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), CODE_REMOVED);
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn shows_settings_with_their_source_and_the_rule_set() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("pkg")).unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\npreserve_patterns = [\"SAFETY\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("pkg/.uncommentrc.toml"),
+        "[global]\nremove_todos = true\nuse_default_ignores = false\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["config", "show", "pkg/new.py"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let line = |key: &str| stdout.lines().find(|line| line.trim_start().starts_with(key)).unwrap();
+    assert!(line("remove_todos").contains("true"), "{stdout}");
+    assert!(line("remove_todos").ends_with("pkg/.uncommentrc.toml"), "{stdout}");
+    assert!(line("preserve_patterns").contains("\"SAFETY\""), "{stdout}");
+    assert!(!line("preserve_patterns").contains("pkg"), "{stdout}");
+    assert!(line("language").contains("python"), "{stdout}");
+
+    assert!(stdout.contains("Preservation rules (6, checked in order)"), "{stdout}");
+    assert!(stdout.contains("remove_fixme = false ("), "{stdout}");
+    assert!(stdout.contains("    pattern \"SAFETY\""), "{stdout}");
+    assert!(!stdout.contains("pattern \"TODO\""), "{stdout}");
+}
+
+#[test]
+fn rejects_a_directory() {
+    let dir = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["config", "show", "."])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is a directory"));
+}
+
+#[test]
+fn honors_the_config_flag_over_discovered_files() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".uncommentrc.toml"), "[global]\nremove_todos = false\n").unwrap();
+    fs::write(dir.path().join("ci.toml"), "[global]\nremove_todos = true\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--config", "ci.toml", "config", "show", "a.py"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let remove_todos = stdout
+        .lines()
+        .find(|line| line.trim_start().starts_with("remove_todos"))
+        .unwrap();
+    assert!(remove_todos.contains("true"), "{stdout}");
+    assert!(!stdout.contains(".uncommentrc.toml"), "{stdout}");
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn detects_extensionless_scripts_and_modelines_while_walking() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("bin")).unwrap();
+    fs::create_dir_all(dir.path().join("tools")).unwrap();
+    fs::write(
+        dir.path().join("bin/deploy"),
+        "#!/usr/bin/env python3\n# remove me\nprint('deploy')\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("tools/run"), "#!/bin/bash\n# remove me\necho run\n").unwrap();
+    fs::write(dir.path().join("values.tmpl"), "# vim: ft=yaml\n# remove me\nkey: 1\n").unwrap();
+    fs::write(dir.path().join("NOTES"), "# just text\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([".", "--no-journal", "--remove-doc"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("bin/deploy")).unwrap(),
+        "#!/usr/bin/env python3\nprint('deploy')\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("tools/run")).unwrap(),
+        "#!/bin/bash\necho run\n"
+    );
+    assert_eq!(fs::read_to_string(dir.path().join("values.tmpl")).unwrap(), "key: 1\n");
+    assert_eq!(fs::read_to_string(dir.path().join("NOTES")).unwrap(), "# just text\n");
+}
+
+#[test]
+fn leaves_version_control_hooks_alone() {
+    let dir = TempDir::new().unwrap();
+    let status = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let hooks = dir.path().join(".git/hooks");
+    fs::create_dir_all(&hooks).unwrap();
+    let hook = "#!/bin/sh\n# keep me\nexit 0\n";
+    fs::write(hooks.join("pre-commit"), hook).unwrap();
+    fs::write(hooks.join("pre-commit.sample"), hook).unwrap();
+    fs::create_dir_all(dir.path().join(".hg")).unwrap();
+    fs::write(dir.path().join(".hg/hook.py"), "# keep me\n").unwrap();
+    fs::write(dir.path().join("deploy"), "#!/bin/sh\n# remove me\necho deploy\n").unwrap();
+
+    for extra in [None, Some("--no-gitignore")] {
+        let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+            .args([".", "--no-journal"])
+            .args(extra)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        assert_eq!(fs::read_to_string(hooks.join("pre-commit")).unwrap(), hook);
+        assert_eq!(fs::read_to_string(hooks.join("pre-commit.sample")).unwrap(), hook);
+        assert_eq!(
+            fs::read_to_string(dir.path().join(".hg/hook.py")).unwrap(),
+            "# keep me\n"
+        );
+    }
+    assert_eq!(
+        fs::read_to_string(dir.path().join("deploy")).unwrap(),
+        "#!/bin/sh\necho deploy\n"
+    );
+}
+
+#[test]
+fn sniffs_stdin_content_when_the_virtual_name_has_no_extension() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["-", "--stdin-filepath", "bin/tool"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"#!/usr/bin/env ruby\n# remove me\nputs 1\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "#!/usr/bin/env ruby\nputs 1\n"
+    );
+}
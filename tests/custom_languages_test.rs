@@ -163,10 +163,14 @@ fn test_vuejs_configuration() {
         comment_nodes: vec!["comment".to_string()],
         doc_comment_nodes: vec![],
         preserve_patterns: vec!["eslint-".to_string(), "@ts-".to_string()],
+        remove_patterns: vec![],
+        preserve_regex: vec![],
+        remove_regex: vec![],
         remove_todos: None,
         remove_fixme: None,
         remove_docs: None,
         use_default_ignores: None,
+        only_commented_code: None,
     };
 
     assert_eq!(config.name, "Vue");
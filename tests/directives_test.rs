@@ -0,0 +1,105 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn run_on(dir: &TempDir, file: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([file, "--no-journal"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::read_to_string(dir.path().join(file)).unwrap()
+}
+
+#[test]
+fn keeps_every_comment_of_an_off_on_region() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# before\n# uncomment:off\nx = 1  # inside\n\n# also inside\n# uncomment:on\ny = 2  # after\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        run_on(&dir, "a.py"),
+        "# uncomment:off\nx = 1  # inside\n\n# also inside\n# uncomment:on\ny = 2  \n"
+    );
+}
+
+#[test]
+fn region_markers_work_in_block_comments() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.js"),
+        "const a = 1; // gone\n/* uncomment:off */\nconst b = 2; // stays\n/* uncomment:on */\nconst c = 3; // gone\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        run_on(&dir, "a.js"),
+        "const a = 1; \n/* uncomment:off */\nconst b = 2; // stays\n/* uncomment:on */\nconst c = 3; \n"
+    );
+}
+
+#[test]
+fn unterminated_region_runs_to_the_end_of_the_file() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.rs"),
+        "// gone\n// uncomment:off\nfn f() {} // stays\n",
+    )
+    .unwrap();
+
+    assert_eq!(run_on(&dir, "a.rs"), "// uncomment:off\nfn f() {} // stays\n");
+}
+
+#[test]
+fn keep_next_skips_blank_lines_but_only_keeps_one_comment() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# uncomment:keep-next\n\n# kept\n# gone\nx = 1\n",
+    )
+    .unwrap();
+
+    assert_eq!(run_on(&dir, "a.py"), "# uncomment:keep-next\n\n# kept\nx = 1\n");
+}
+
+#[test]
+fn directives_override_removal_rules() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_patterns = [\"TODO\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# TODO: gone\n# uncomment:keep-next\n# TODO: kept\nx = 1\n",
+    )
+    .unwrap();
+
+    assert_eq!(run_on(&dir, "a.py"), "# uncomment:keep-next\n# TODO: kept\nx = 1\n");
+}
+
+#[test]
+fn near_miss_markers_are_ordinary_comments() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# uncomment:offset notes\nx = 1  # gone\n# uncomment:keep-nextline\n# gone too\n",
+    )
+    .unwrap();
+    assert_eq!(run_on(&dir, "a.py"), "x = 1  \n");
+
+    fs::write(
+        dir.path().join("a.rs"),
+        "// uncomment:off\nfn f() {} // stays\n// uncomment:only-commented-code=true\nfn g() {} // stays too\n",
+    )
+    .unwrap();
+    assert_eq!(
+        run_on(&dir, "a.rs"),
+        "// uncomment:off\nfn f() {} // stays\n// uncomment:only-commented-code=true\nfn g() {} // stays too\n"
+    );
+}
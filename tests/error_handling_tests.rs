@@ -75,10 +75,14 @@ fn test_config_validation_edge_cases() {
         comment_nodes: vec!["comment".to_string()],
         doc_comment_nodes: vec![],
         preserve_patterns: vec![],
+        remove_patterns: vec![],
+        preserve_regex: vec![],
+        remove_regex: vec![],
         remove_todos: None,
         remove_fixme: None,
         remove_docs: None,
         use_default_ignores: None,
+        only_commented_code: None,
     };
 
     config.languages.insert("empty_name".to_string(), invalid_language);
@@ -93,10 +97,14 @@ fn test_config_validation_edge_cases() {
         comment_nodes: vec![],
         doc_comment_nodes: vec![],
         preserve_patterns: vec![],
+        remove_patterns: vec![],
+        preserve_regex: vec![],
+        remove_regex: vec![],
         remove_todos: None,
         remove_fixme: None,
         remove_docs: None,
         use_default_ignores: None,
+        only_commented_code: None,
     };
 
     config2
@@ -0,0 +1,89 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn explain(dir: &TempDir, target: &str) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["explain", target])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned() + &String::from_utf8_lossy(&output.stderr),
+    )
+}
+
+#[test]
+fn reports_rule_setting_and_config_file_for_each_comment() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_fixme = true\npreserve_patterns = [\"SAFETY\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("a.rs"),
+        "// SAFETY: checked\n// FIXME: drop\n// plain\n\n// one\n// two ~keep\nfn f() {}\n",
+    )
+    .unwrap();
+
+    let (ok, output) = explain(&dir, "a.rs");
+    assert!(ok, "{output}");
+
+    let lines: Vec<&str> = output.lines().collect();
+    let reason_for = |comment: &str| {
+        let index = lines.iter().position(|line| line.contains(comment)).unwrap();
+        (lines[index], lines[index + 1])
+    };
+
+    let (decision, reason) = reason_for("// SAFETY: checked");
+    assert!(decision.contains("kept"));
+    assert!(reason.contains("preserve_patterns += \"SAFETY\""), "{reason}");
+    assert!(reason.contains(".uncommentrc.toml"), "{reason}");
+
+    let (decision, reason) = reason_for("// FIXME: drop");
+    assert!(decision.contains("removed"));
+    assert!(reason.contains("remove_fixme = true"), "{reason}");
+
+    let (decision, reason) = reason_for("// plain");
+    assert!(decision.contains("removed"));
+    assert!(reason.contains("no preservation rule matches"), "{reason}");
+
+    let (decision, reason) = reason_for("// one");
+    assert!(decision.contains("kept"));
+    assert!(reason.contains("~keep on another line"), "{reason}");
+
+    assert!(output.contains("5 comments: 3 kept, 2 removed"), "{output}");
+}
+
+#[test]
+fn narrows_to_one_line() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.py"), "# first\nx = 1  # second\n").unwrap();
+
+    let (ok, output) = explain(&dir, "a.py:2");
+    assert!(ok, "{output}");
+    assert!(output.contains("# second"));
+    assert!(!output.contains("# first"));
+
+    let (ok, output) = explain(&dir, "a.py:9");
+    assert!(ok);
+    assert!(output.contains("No comment on line 9."));
+}
+
+#[test]
+fn honors_the_config_flag() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("ci.toml"), "[global]\nremove_todos = true\n").unwrap();
+    fs::write(dir.path().join("a.py"), "# TODO: drop\nx = 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--config", "ci.toml", "explain", "a.py:1"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("0 kept, 1 removed"), "{stdout}");
+}
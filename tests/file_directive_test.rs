@@ -0,0 +1,113 @@
+use std::fs;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn run(dir: &TempDir, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(args)
+        .current_dir(dir.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn ignore_file_leaves_the_file_untouched() {
+    let dir = TempDir::new().unwrap();
+    let source = "// uncomment: ignore-file\nfn f() {} // stays\n";
+    fs::write(dir.path().join("a.rs"), source).unwrap();
+    fs::write(dir.path().join("b.rs"), "fn g() {} // goes\n").unwrap();
+
+    let output = run(&dir, &[".", "--no-journal"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), source);
+    assert_eq!(fs::read_to_string(dir.path().join("b.rs")).unwrap(), "fn g() {} \n");
+}
+
+#[test]
+fn settings_override_config_files_for_that_file_only() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".uncommentrc.toml"), "[global]\nremove_todos = true\n").unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "#!/usr/bin/env python3\n# uncomment: remove-todos=false, remove-docs=true\n\"\"\"Docs.\"\"\"\n# TODO: stays\nx = 1\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("b.py"), "\"\"\"Docs.\"\"\"\n# TODO: goes\nx = 1\n").unwrap();
+
+    let output = run(&dir, &[".", "--no-journal"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.py")).unwrap(),
+        "#!/usr/bin/env python3\n# uncomment: remove-todos=false, remove-docs=true\n# TODO: stays\nx = 1\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("b.py")).unwrap(),
+        "\"\"\"Docs.\"\"\"\nx = 1\n"
+    );
+}
+
+#[test]
+fn explain_and_config_show_name_the_directive() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# uncomment: remove-todos=true\n# TODO: goes\nx = 1\n",
+    )
+    .unwrap();
+
+    let explain = run(&dir, &["explain", "a.py:2"]);
+    let explain = String::from_utf8_lossy(&explain.stdout);
+    assert!(
+        explain.contains("remove_todos = true (uncomment: directive at a.py:1)"),
+        "{explain}"
+    );
+
+    let show = run(&dir, &["config", "show", "a.py"]);
+    let show = String::from_utf8_lossy(&show.stdout);
+    assert!(show.contains("uncomment: directive at a.py:1"), "{show}");
+}
+
+#[test]
+fn warns_about_an_invalid_directive_and_skips_it() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "# uncomment: remove-docs=maybe\n# note\nx = 1\n",
+    )
+    .unwrap();
+
+    let output = run(&dir, &["a.py", "--no-journal"]);
+
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Warning: Ignoring invalid uncomment directive in a.py: line 1: `remove-docs` must be true or false"
+        ),
+        "{stderr}"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.py")).unwrap(),
+        "# uncomment: remove-docs=maybe\nx = 1\n"
+    );
+}
+
+#[test]
+fn ignores_directive_text_in_strings_and_urls() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "MSG = \"uncomment: keep=yes\"\n# see https://x/uncomment: ignore-file\nx = 1\n",
+    )
+    .unwrap();
+
+    let output = run(&dir, &["a.py", "--no-journal"]);
+
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.py")).unwrap(),
+        "MSG = \"uncomment: keep=yes\"\nx = 1\n"
+    );
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn run(dir: &TempDir, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(args)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+fn git(dir: &TempDir, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir.path())
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+const GO_SOURCE: &str = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api // x\n";
+const PB2_SOURCE: &str = "# x\nx = 1\n";
+
+fn write_tree(dir: &TempDir) {
+    fs::write(dir.path().join("api.pb.go"), GO_SOURCE).unwrap();
+    fs::write(dir.path().join("service.go"), GO_SOURCE).unwrap();
+    fs::write(dir.path().join("api_pb2.py"), PB2_SOURCE).unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {} // gone\n").unwrap();
+}
+
+#[test]
+fn skips_generated_files_and_lists_them_in_the_summary() {
+    let dir = TempDir::new().unwrap();
+    write_tree(&dir);
+
+    let output = run(
+        &dir,
+        &["api.pb.go", "service.go", "api_pb2.py", "main.rs", "--no-journal"],
+    );
+
+    assert_eq!(fs::read_to_string(dir.path().join("service.go")).unwrap(), GO_SOURCE);
+    assert_eq!(fs::read_to_string(dir.path().join("api_pb2.py")).unwrap(), PB2_SOURCE);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+        "fn main() {} \n"
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 files processed"), "{stdout}");
+    assert!(stdout.contains("Skipped 3 generated file(s)"), "{stdout}");
+    assert!(stdout.contains("api.pb.go (name ends with .pb.go)"), "{stdout}");
+    assert!(
+        stdout.contains("service.go (header says \"code generated\")"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn include_generated_processes_them() {
+    let dir = TempDir::new().unwrap();
+    write_tree(&dir);
+
+    run(
+        &dir,
+        &["service.go", "api_pb2.py", "--include-generated", "--no-journal"],
+    );
+
+    assert_eq!(fs::read_to_string(dir.path().join("api_pb2.py")).unwrap(), "x = 1\n");
+}
+
+#[test]
+fn honours_linguist_generated_in_a_git_repository() {
+    let dir = TempDir::new().unwrap();
+    git(&dir, &["init", "-q"]);
+    fs::write(dir.path().join(".gitattributes"), "vendor/** linguist-generated\n").unwrap();
+    fs::create_dir(dir.path().join("vendor")).unwrap();
+    fs::write(dir.path().join("vendor/lib.rs"), "fn f() {} // stays\n").unwrap();
+
+    let output = run(&dir, &["vendor/lib.rs", "--dry-run", "--output-format", "json"]);
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["total_files"], 0);
+    assert_eq!(report["summary"]["skipped_generated"][0], "vendor/lib.rs");
+}
+
+#[test]
+fn walks_a_directory_outside_a_git_repository() {
+    let dir = TempDir::new().unwrap();
+    write_tree(&dir);
+
+    let output = run(&dir, &[".", "--no-journal"]);
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+        "fn main() {} \n"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Skipped 3 generated file(s)"), "{stdout}");
+}
+
+#[test]
+fn checks_attributes_of_thousands_of_files_without_hanging() {
+    let dir = TempDir::new().unwrap();
+    git(&dir, &["init", "-q"]);
+    fs::write(dir.path().join(".gitattributes"), "gen/** linguist-generated\n").unwrap();
+    for directory in ["src", "gen"] {
+        fs::create_dir(dir.path().join(directory)).unwrap();
+        for i in 0..1500 {
+            fs::write(
+                dir.path().join(format!("{directory}/module_{i:04}.rs")),
+                "fn f() {} // x\n",
+            )
+            .unwrap();
+        }
+    }
+
+    let child = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([".", "--dry-run", "--output-format", "json"])
+        .current_dir(dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let pid = child.id();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || sender.send(child.wait_with_output().unwrap()));
+    let Ok(output) = receiver.recv_timeout(Duration::from_secs(60)) else {
+        Command::new("kill").arg(pid.to_string()).status().unwrap();
+        panic!("uncomment did not finish within 60s");
+    };
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["total_files"], 1500);
+    assert_eq!(report["summary"]["skipped_generated"].as_array().unwrap().len(), 1500);
+}
+
+#[test]
+fn staged_runs_judge_the_staged_content() {
+    let dir = TempDir::new().unwrap();
+    git(&dir, &["init", "-q"]);
+    fs::write(dir.path().join("service.go"), GO_SOURCE).unwrap();
+    git(&dir, &["add", "service.go"]);
+    fs::write(dir.path().join("service.go"), "package api // x\n").unwrap();
+
+    let output = run(&dir, &["--staged", "--output-format", "json"]);
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["summary"]["skipped_generated"][0], "service.go");
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn prints_the_tree_with_comment_nodes_marked() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("a.py"),
+        "def f():\n    \"\"\"Docs.\"\"\"\n    return 1  # one\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["inspect", "a.py"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(
+        lines[1].contains("comment nodes: comment · doc nodes: string"),
+        "{stdout}"
+    );
+    assert!(lines[2].starts_with("module  [0.."), "{stdout}");
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.trim_start().starts_with("comment  [") && line.ends_with("◆ comment")),
+        "{stdout}"
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.trim_start().starts_with("string  [") && line.ends_with("◆ doc comment")),
+        "{stdout}"
+    );
+    assert!(
+        !stdout.contains("\"def\""),
+        "anonymous nodes are hidden by default: {stdout}"
+    );
+
+    let all = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["inspect", "a.py", "--all"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(all.stdout).unwrap().contains("\"def\""));
+}
@@ -439,13 +439,16 @@ def hello(): pass"#;
     let src_result = fs::read_to_string(&src_file).unwrap();
     let regular_result = fs::read_to_string(&regular_file).unwrap();
 
-    // All should preserve TODO and docs due to global config
-    assert!(test_result.contains("TODO: todo comment"));
-    assert!(test_result.contains("Docstring"));
+    // test_*.py overrides the global config and drops TODOs
+    assert!(!test_result.contains("TODO: todo comment"));
+    // src/*.py adds a preserve pattern on top of the global config
     assert!(src_result.contains("TODO: todo comment"));
     assert!(src_result.contains("Docstring"));
+    assert!(src_result.contains("PRODUCTION: prod comment"));
+    // Files no pattern matches follow the global config
     assert!(regular_result.contains("TODO: todo comment"));
     assert!(regular_result.contains("Docstring"));
+    assert!(!regular_result.contains("PRODUCTION"));
 }
 
 #[test]
@@ -0,0 +1,106 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn language_flag_processes_files_without_a_known_extension() {
+    let dir = TempDir::new().unwrap();
+    let script = dir.path().join("deploy");
+    fs::write(&script, "#!/usr/bin/env python3\n# remove me\nprint('hi')\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--language", "python", "--no-journal"])
+        .arg(&script)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(&script).unwrap(),
+        "#!/usr/bin/env python3\nprint('hi')\n"
+    );
+}
+
+#[test]
+fn language_flag_overrides_a_misleading_extension() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("values.tmpl");
+    fs::write(&file, "# remove me\nkey: value\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--language", "yml", "--dry-run", "--output-format", "json"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["language"], "yaml");
+    assert_eq!(report["files"][0]["comments_removed"], 1);
+}
+
+#[test]
+fn language_flag_leaves_walked_files_to_detection() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("deploy"), "# remove me\nprint('hi')\n").unwrap();
+    fs::write(dir.path().join("README.md"), "# Title\n\nText\n").unwrap();
+    fs::write(dir.path().join("config.yml"), "# remove me\nkey: value\n").unwrap();
+    fs::write(dir.path().join("NOTES"), "# just text\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--language", "python", "--no-journal", "deploy", "."])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(dir.path().join("deploy")).unwrap(), "print('hi')\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("README.md")).unwrap(),
+        "# Title\n\nText\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("config.yml")).unwrap(),
+        "key: value\n"
+    );
+    assert_eq!(fs::read_to_string(dir.path().join("NOTES")).unwrap(), "# just text\n");
+}
+
+#[test]
+fn config_patterns_pick_the_language_for_matching_paths() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[patterns.\"**/*.inc\"]\nlanguage = \"php\"\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("lib")).unwrap();
+    let include = dir.path().join("lib/helpers.inc");
+    fs::write(&include, "<?php\n// remove me\n$x = 1;\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([".", "--no-journal"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&include).unwrap(), "<?php\n$x = 1;\n");
+}
+
+#[test]
+fn unknown_language_is_a_usage_error() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("a.txt");
+    fs::write(&file, "# keep\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--language", "klingon"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown language"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "# keep\n");
+}
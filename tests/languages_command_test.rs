@@ -0,0 +1,55 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn lists_builtin_and_configured_languages_as_json() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[languages.python]\nname = \"Python\"\nextensions = [\".py\", \".pyz\"]\ncomment_nodes = [\"comment\"]\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["languages", "--json"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let languages: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let find = |name: &str| languages.iter().find(|language| language["name"] == name).unwrap();
+
+    let rust = find("rust");
+    assert_eq!(rust["source"], "built-in");
+    assert_eq!(rust["tslp_name"], "rust");
+    assert!(
+        rust["comment_types"]
+            .as_array()
+            .unwrap()
+            .contains(&"line_comment".into())
+    );
+    assert!(rust["handler"].is_null());
+
+    let python = find("python");
+    assert_eq!(python["source"], "config");
+    assert_eq!(python["handler"], "python");
+    assert!(python["extensions"].as_array().unwrap().contains(&".pyz".into()));
+}
+
+#[test]
+fn prints_a_readable_listing() {
+    let dir = TempDir::new().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg("languages")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("comment nodes:"));
+    assert!(stdout.contains("handler: c-family"));
+}
@@ -0,0 +1,69 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+const SOURCE: &str = "\
+// line 1
+fn a() {}
+// line 3
+/* lines 4
+   to 5 */
+    // line 6
+// line 7
+";
+
+#[test]
+fn removes_only_comments_fully_inside_the_ranges() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("a.rs");
+    fs::write(&file, SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--lines", "3:4", "--lines", "6", "--no-journal"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Lines 3 and 6 go; the block straddling 4:5 and the comments outside the
+    // selection stay.
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "// line 1\nfn a() {}\n/* lines 4\n   to 5 */\n// line 7\n"
+    );
+}
+
+#[test]
+fn applies_to_stdin_for_editor_selections() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["-", "--stdin-filepath", "a.rs", "--lines", "1:2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(SOURCE.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        SOURCE.replacen("// line 1\n", "", 1)
+    );
+}
+
+#[test]
+fn requires_a_single_file() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.rs"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--lines", "1:2"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--lines"));
+    assert_eq!(fs::read_to_string(dir.path().join("a.rs")).unwrap(), SOURCE);
+}
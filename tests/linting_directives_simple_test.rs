@@ -15,10 +15,13 @@ fn process_code(content: &str, extension: &str, remove_todo: bool) -> String {
         remove_doc: false,
         custom_preserve_patterns: vec![],
         use_default_ignores: true,
+        only_commented_code: false,
         dry_run: false,
         show_diff: false,
         respect_gitignore: false,
         traverse_git_repos: false,
+        line_filter: None,
+        language: None,
     };
 
     let mut processor = Processor::new_with_config(&config_manager);
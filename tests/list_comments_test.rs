@@ -0,0 +1,83 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const SOURCE: &str = "\"\"\"Counter helpers.\"\"\"\n# TODO: keep\ndef inc(x):\n    return x + 1  # plain, \"quoted\"\n";
+
+fn list_comments(dir: &TempDir, format: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["a.py", "--list-comments", format])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn lists_every_comment_as_json_without_modifying_files() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.py"), SOURCE).unwrap();
+
+    let records: serde_json::Value = serde_json::from_str(&list_comments(&dir, "json")).unwrap();
+    let summary: Vec<(&str, u64, bool, bool, &str)> = records
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|record| {
+            (
+                record["path"].as_str().unwrap(),
+                record["start_row"].as_u64().unwrap(),
+                record["is_documentation"].as_bool().unwrap(),
+                record["preserved"].as_bool().unwrap(),
+                record["text"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("a.py", 0, true, true, "\"\"\"Counter helpers.\"\"\""),
+            ("a.py", 1, false, true, "# TODO: keep"),
+            ("a.py", 3, false, false, "# plain, \"quoted\""),
+        ]
+    );
+    assert_eq!(records[1]["language"], "python");
+    assert_eq!(records[1]["start_byte"], 23);
+    assert_eq!(records[1]["end_byte"], 35);
+    assert_eq!(records[1]["node_type"], "comment");
+
+    assert_eq!(fs::read_to_string(dir.path().join("a.py")).unwrap(), SOURCE);
+    assert!(!dir.path().join(".uncomment").exists());
+}
+
+#[test]
+fn lists_comments_as_csv() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.py"), SOURCE).unwrap();
+
+    let csv = list_comments(&dir, "csv");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "path,language,start_byte,end_byte,start_row,end_row,node_type,is_documentation,preserved,text"
+    );
+    assert_eq!(lines[2], "a.py,python,23,35,1,1,comment,false,true,# TODO: keep");
+    assert_eq!(
+        lines[3],
+        "a.py,python,66,83,3,3,comment,false,false,\"# plain, \"\"quoted\"\"\""
+    );
+}
+
+#[test]
+fn conflicts_with_writing_modes() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.py"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["a.py", "--list-comments", "json", "--patch", "out.patch"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
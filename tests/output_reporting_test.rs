@@ -1,22 +1,14 @@
 use std::fs;
-use std::path::PathBuf;
 use std::process::{Command, Output};
 use tempfile::TempDir;
 
-fn uncomment_binary() -> PathBuf {
-    std::env::current_exe()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .join("uncomment")
-}
-
 fn run(file: &std::path::Path, args: &[&str]) -> Output {
     let mut all_args: Vec<&str> = vec![file.to_str().unwrap()];
     all_args.extend_from_slice(args);
-    Command::new(uncomment_binary()).args(&all_args).output().unwrap()
+    Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(&all_args)
+        .output()
+        .unwrap()
 }
 
 #[test]
@@ -179,3 +171,113 @@ fn diff_works_without_dry_run() {
         "file should be modified on disk, got: {contents}"
     );
 }
+
+#[test]
+fn check_fails_without_writing_when_comments_would_be_removed() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("sample.js");
+    let original = "// strip me\nconst x = 1;\n";
+    fs::write(&file, original).unwrap();
+
+    let output = run(&file, &["--check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(3), "expected exit 3, stderr: {stderr}");
+    assert!(stdout.contains("Would modify:"), "expected file listing, got: {stdout}");
+    assert!(stdout.contains("sample.js"), "expected file path, got: {stdout}");
+    assert!(
+        stderr.contains("would be modified"),
+        "expected failure reason, got: {stderr}"
+    );
+    assert_eq!(fs::read_to_string(&file).unwrap(), original, "--check must never write");
+}
+
+#[test]
+fn check_succeeds_when_nothing_would_be_removed() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("sample.js");
+    fs::write(&file, "// TODO: keep me\nconst x = 1;\n").unwrap();
+
+    let output = run(&file, &["--check"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "expected success, stderr: {stderr}");
+}
+
+#[test]
+fn json_output_is_a_single_parseable_document() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("sample.js");
+    fs::write(&file, "// first\nconst x = 1;\n").unwrap();
+
+    let output = run(&file, &["--dry-run", "--output-format", "json"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "run failed: {stdout}");
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("stdout should be pure JSON");
+    let record = &report["files"][0];
+    assert_eq!(record["language"], "javascript");
+    assert_eq!(record["comments_removed"], 1);
+    assert_eq!(record["removed_comments"][0]["start_row"], 0);
+    assert_eq!(record["removed_comments"][0]["preview"], "// first");
+    assert_eq!(report["summary"]["modified_files"], 1);
+    assert_eq!(report["summary"]["dry_run"], true);
+}
+
+#[test]
+fn ndjson_output_reports_errors_per_file_and_ends_with_summary() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("good.js"), "// strip\nconst x = 1;\n").unwrap();
+    fs::write(dir.path().join("bad.js"), [0xff, 0xfe, b'\n']).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args([dir.path().to_str().unwrap(), "--dry-run", "--output-format", "ndjson"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let records: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be JSON"))
+        .collect();
+    assert_eq!(records.len(), 3, "got: {stdout}");
+    let bad = records
+        .iter()
+        .find(|r| r["path"].as_str().unwrap().ends_with("bad.js"))
+        .unwrap();
+    assert_eq!(bad["type"], "file");
+    assert!(bad["error"].is_string());
+    let summary = records.last().unwrap();
+    assert_eq!(summary["type"], "summary");
+    assert_eq!(summary["total_files"], 2);
+    assert_eq!(summary["failed_files"], 1);
+    assert_eq!(summary["comments_removed"], 1);
+}
+
+#[test]
+fn sarif_output_locates_comments_and_flags_directives() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("sample.js");
+    fs::write(&file, "// plain\nconst x = 1; // eslint-disable-line\n").unwrap();
+
+    let output = run(
+        &file,
+        &["--dry-run", "--no-default-ignores", "--output-format", "sarif"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "run failed: {stdout}");
+    let log: serde_json::Value = serde_json::from_str(&stdout).expect("stdout should be a SARIF log");
+    assert_eq!(log["version"], "2.1.0");
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["level"], "note");
+    assert_eq!(results[1]["level"], "warning");
+    let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 2);
+    assert_eq!(region["startColumn"], 14);
+    let deleted = &results[0]["fixes"][0]["artifactChanges"][0]["replacements"][0]["deletedRegion"];
+    assert_eq!(deleted["byteOffset"], 0);
+    assert_eq!(deleted["byteLength"], "// plain\n".len());
+}
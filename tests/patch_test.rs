@@ -0,0 +1,67 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const SOURCE: &str = "// leading comment\nfn main() {\n    let a = 1; // trailing\n    /* block\n       spanning */\n    let b = 2;\n}\n// last line, no newline";
+
+#[test]
+fn patch_applies_with_git_apply_and_matches_a_real_run() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["src", "--patch", "changes.patch"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("src/main.rs")).unwrap(),
+        SOURCE,
+        "--patch must not modify sources"
+    );
+
+    let patch = fs::read_to_string(dir.path().join("changes.patch")).unwrap();
+    assert!(patch.starts_with("diff --git a/src/main.rs b/src/main.rs\n"), "{patch}");
+
+    let applied = Command::new("git")
+        .args(["apply", "--verbose", "changes.patch"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        applied.status.success(),
+        "git apply failed: {}",
+        String::from_utf8_lossy(&applied.stderr)
+    );
+    let patched = fs::read_to_string(dir.path().join("src/main.rs")).unwrap();
+
+    fs::write(dir.path().join("src/main.rs"), SOURCE).unwrap();
+    let real_run = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg("src/main.rs")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(real_run.status.success());
+    assert_eq!(patched, fs::read_to_string(dir.path().join("src/main.rs")).unwrap());
+}
+
+#[test]
+fn patch_to_stdout_contains_only_the_patch() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.py"), "# strip\nx = 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["a.py", "--patch", "-"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert_eq!(
+        stdout,
+        "diff --git a/a.py b/a.py\n--- a/a.py\n+++ b/a.py\n@@ -1,2 +1,1 @@\n-# strip\n x = 1\n"
+    );
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+const SOURCE: &str = "/// Adds one.\nfn inc(x: i32) -> i32 {\n    // TODO: overflow\n    x + 1\n}\n";
+
+#[test]
+fn pattern_settings_apply_only_to_matching_paths() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_docs = true\nremove_todos = true\n\n\
+         [patterns.\"tests/**\"]\nremove_docs = false\nremove_todos = false\n",
+    )
+    .unwrap();
+    fs::create_dir_all(dir.path().join("tests/unit")).unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("tests/unit/inc.rs"), SOURCE).unwrap();
+    fs::write(dir.path().join("src/inc.rs"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg(".")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("tests/unit/inc.rs")).unwrap(),
+        SOURCE
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("src/inc.rs")).unwrap(),
+        "fn inc(x: i32) -> i32 {\n    x + 1\n}\n"
+    );
+}
+
+#[test]
+fn pattern_settings_win_over_language_settings() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[languages.rust]\nname = \"Rust\"\nextensions = [\".rs\"]\n\
+         comment_nodes = [\"line_comment\", \"block_comment\"]\nremove_todos = true\n\n\
+         [patterns.\"keep/*.rs\"]\nremove_todos = false\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("keep")).unwrap();
+    fs::write(dir.path().join("keep/inc.rs"), SOURCE).unwrap();
+    fs::write(dir.path().join("inc.rs"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg(".")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert!(
+        fs::read_to_string(dir.path().join("keep/inc.rs"))
+            .unwrap()
+            .contains("// TODO: overflow")
+    );
+    assert!(!fs::read_to_string(dir.path().join("inc.rs")).unwrap().contains("TODO"));
+}
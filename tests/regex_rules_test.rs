@@ -0,0 +1,73 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn run(dir: &TempDir) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg(".")
+        .current_dir(dir.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn preserve_regex_matches_whole_words_ignoring_case() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_todos = true\n\
+         preserve_regex = [{ pattern = \"todo\", whole_word = true, case_insensitive = true }]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        "// Todo: tidy\n// TODOS are bad\n// mastodon TODOlist\nfn f() {}\n",
+    )
+    .unwrap();
+
+    let output = run(&dir);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+        "// Todo: tidy\nfn f() {}\n"
+    );
+}
+
+#[test]
+fn remove_regex_overrides_preservation_except_keep() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[languages.rust]\nname = \"Rust\"\nextensions = [\".rs\"]\n\
+         comment_nodes = [\"line_comment\", \"block_comment\"]\nremove_regex = ['@author\\b']\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        "/// Adds one.\n/// @author someone\nfn inc(x: i32) -> i32 {\n    // TODO(@author): overflow ~keep\n    x + 1\n}\n",
+    )
+    .unwrap();
+
+    let output = run(&dir);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+        "/// Adds one.\nfn inc(x: i32) -> i32 {\n    // TODO(@author): overflow ~keep\n    x + 1\n}\n"
+    );
+}
+
+#[test]
+fn invalid_regex_is_reported_with_the_config_file() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\npreserve_regex = [\"(unclosed\"]\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn f() {}\n").unwrap();
+
+    let output = run(&dir);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(".uncommentrc.toml"), "{stderr}");
+    assert!(stderr.contains("invalid regex"), "{stderr}");
+}
@@ -0,0 +1,63 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn remove_patterns_override_default_ignores_except_keep() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_patterns = [\"@author\"]\n\n\
+         [languages.javascript]\nname = \"JavaScript\"\nextensions = [\".js\"]\n\
+         comment_nodes = [\"comment\"]\nremove_patterns = [\"eslint-disable\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("app.js"),
+        "// @author someone\n// @deprecated use g\n// eslint-disable-next-line no-console\nconsole.log(1);\n\n\
+         // @author me ~keep\nconsole.log(2);\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("lib.rs"),
+        "// eslint-disable\n// @author someone\nfn f() {}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg(".")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("app.js")).unwrap(),
+        "// @deprecated use g\nconsole.log(1);\n\n// @author me ~keep\nconsole.log(2);\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+        "// eslint-disable\nfn f() {}\n"
+    );
+}
+
+#[test]
+fn explain_names_the_removal_pattern() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".uncommentrc.toml"),
+        "[global]\nremove_patterns = [\"@author\"]\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("lib.rs"), "// @author someone\nfn f() {}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["explain", "lib.rs:1"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("removal rule pattern \"@author\""), "{stdout}");
+    assert!(stdout.contains("remove_patterns += \"@author\""), "{stdout}");
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git").args(args).current_dir(dir).output().unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// A repository whose only commit holds `a.py` with two legacy comments.
+fn repo_with_history() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+    fs::write(dir.path().join("a.py"), "# legacy one\nx = 1\n# legacy two\ny = 2\n").unwrap();
+    git(dir.path(), &["add", "a.py"]);
+    git(dir.path(), &["commit", "-q", "-m", "initial"]);
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn only_strips_comments_on_changed_lines() {
+    let dir = repo_with_history();
+    fs::write(
+        dir.path().join("a.py"),
+        "# legacy one\nx = 1\n# new noise\n# legacy two\ny = 2\n    # new and indented\n",
+    )
+    .unwrap();
+
+    let output = run(dir.path(), &["--since", "HEAD", "--no-journal", "."]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.py")).unwrap(),
+        "# legacy one\nx = 1\n# legacy two\ny = 2\n"
+    );
+}
+
+#[test]
+fn treats_untracked_files_as_changed_and_skips_untouched_ones() {
+    let dir = repo_with_history();
+    fs::write(dir.path().join("b.py"), "# brand new\nz = 3\n").unwrap();
+
+    let output = run(dir.path(), &["--since", "HEAD", "--no-journal", "."]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    assert_eq!(fs::read_to_string(dir.path().join("b.py")).unwrap(), "z = 3\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.py")).unwrap(),
+        "# legacy one\nx = 1\n# legacy two\ny = 2\n"
+    );
+}
+
+#[test]
+fn rejects_unknown_revisions() {
+    let dir = repo_with_history();
+
+    let output = run(dir.path(), &["--since", "no-such-branch", "."]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown revision: no-such-branch"));
+}
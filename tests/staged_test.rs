@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(dir).output().unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+    dir
+}
+
+fn run_staged(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg("--staged")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+#[test]
+fn rewrites_index_and_worktree_for_fully_staged_files() {
+    let dir = repo();
+    fs::write(dir.path().join("a.py"), "# strip\nx = 1\n").unwrap();
+    fs::write(dir.path().join("unstaged.py"), "# untouched\ny = 2\n").unwrap();
+    git(dir.path(), &["add", "a.py"]);
+
+    run_staged(dir.path(), &[]);
+
+    assert_eq!(git(dir.path(), &["show", ":a.py"]), "x = 1\n");
+    assert_eq!(fs::read_to_string(dir.path().join("a.py")).unwrap(), "x = 1\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("unstaged.py")).unwrap(),
+        "# untouched\ny = 2\n"
+    );
+}
+
+#[test]
+fn keeps_unstaged_edits_of_partially_staged_files_out_of_the_index() {
+    let dir = repo();
+    let file = dir.path().join("a.py");
+    fs::write(&file, "# strip\nx = 1\n\n\n\n\ny = 2\n").unwrap();
+    git(dir.path(), &["add", "a.py"]);
+    fs::write(&file, "# strip\nx = 1\n\n\n\n\ny = 2\nz = 3  # unstaged\n").unwrap();
+
+    run_staged(dir.path(), &[]);
+
+    assert_eq!(git(dir.path(), &["show", ":a.py"]), "x = 1\n\n\n\n\ny = 2\n");
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "x = 1\n\n\n\n\ny = 2\nz = 3  # unstaged\n",
+        "staged change carried to the worktree, unstaged edit kept"
+    );
+}
+
+#[test]
+fn check_mode_reads_the_index_without_writing() {
+    let dir = repo();
+    fs::write(dir.path().join("a.py"), "x = 1\n").unwrap();
+    git(dir.path(), &["add", "a.py"]);
+    // The worktree gains a comment, but the staged blob is clean.
+    fs::write(dir.path().join("a.py"), "# new\nx = 1\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(["--staged", "--check"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
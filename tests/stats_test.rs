@@ -0,0 +1,75 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn stats(dir: &TempDir, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .arg("stats")
+        .args(args)
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn write_tree(dir: &TempDir) {
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("src/a.py"),
+        "\"\"\"Docs.\"\"\"\n# TODO: later\nx = 1  # why\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("main.rs"),
+        "// note\nfn main() {\n    // eslint-disable-line\n    let y = 2; /* inline */\n}\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn reports_counts_per_language_and_directory_as_json() {
+    let dir = TempDir::new().unwrap();
+    write_tree(&dir);
+
+    let report: serde_json::Value = serde_json::from_str(&stats(&dir, &[".", "--json"])).unwrap();
+
+    let python = &report["languages"]["python"];
+    assert_eq!(python["files"], 1);
+    assert_eq!(python["comments"], 3);
+    assert_eq!(python["comment_lines"], 3);
+    assert_eq!(python["code_lines"], 1);
+    assert_eq!(python["documentation"], 1);
+    assert_eq!(python["preserved"], 2);
+    assert_eq!(python["removed"], 1);
+    assert_eq!(python["preserved_by"]["todo"], 1);
+    assert_eq!(python["preserved_by"]["documentation"], 1);
+    assert_eq!(python["comment_to_code_ratio"], 3.0);
+
+    let rust = &report["languages"]["rust"];
+    assert_eq!(rust["comments"], 3);
+    assert_eq!(rust["preserved_by"]["default_ignores"], 1);
+    assert_eq!(rust["removed"], 2);
+
+    assert_eq!(report["directories"]["src"]["comments"], 3);
+    assert_eq!(report["directories"]["."]["comments"], 3);
+    assert_eq!(report["total"]["files"], 2);
+    assert_eq!(report["total"]["removed"], 3);
+}
+
+#[test]
+fn prints_a_table_without_modifying_files() {
+    let dir = TempDir::new().unwrap();
+    write_tree(&dir);
+
+    let text = stats(&dir, &["."]);
+
+    assert!(text.contains("Language"), "{text}");
+    assert!(text.contains("kept by: todo 1 · documentation 1"), "{text}");
+    assert!(text.contains("2 files, 6 comments"), "{text}");
+    assert!(
+        fs::read_to_string(dir.path().join("main.rs"))
+            .unwrap()
+            .starts_with("// note")
+    );
+}
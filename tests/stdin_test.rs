@@ -0,0 +1,88 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+fn run_with_stdin(dir: &std::path::Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn streams_processed_source_to_stdout_and_reports_to_stderr() {
+    let dir = TempDir::new().unwrap();
+    let output = run_with_stdin(
+        dir.path(),
+        &["-", "--stdin-filepath", "src/main.rs"],
+        "// strip me\nfn main() {}\n",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "run failed: {stderr}");
+    assert_eq!(stdout, "fn main() {}\n");
+    assert!(stderr.contains("Modified:"), "expected report on stderr, got: {stderr}");
+    assert!(
+        stderr.contains("files processed"),
+        "expected summary on stderr, got: {stderr}"
+    );
+    assert!(
+        !dir.path().join("src/main.rs").exists(),
+        "virtual path must not be created"
+    );
+}
+
+#[test]
+fn echoes_unchanged_source() {
+    let dir = TempDir::new().unwrap();
+    let source = "# TODO: keep\nx = 1\n";
+    let output = run_with_stdin(dir.path(), &["-", "--stdin-filepath", "script.py"], source);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), source);
+}
+
+#[test]
+fn uses_config_resolved_for_the_virtual_path() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("pkg")).unwrap();
+    std::fs::write(
+        dir.path().join("pkg/.uncommentrc.toml"),
+        "[global]\nremove_todos = true\n",
+    )
+    .unwrap();
+
+    let output = run_with_stdin(
+        dir.path(),
+        &["-", "--stdin-filepath", "pkg/lib.rs"],
+        "// TODO: gone\nfn f() {}\n",
+    );
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "fn f() {}\n");
+}
+
+#[test]
+fn requires_stdin_filepath() {
+    let dir = TempDir::new().unwrap();
+    let output = run_with_stdin(dir.path(), &["-"], "// x\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("--stdin-filepath"), "got: {stderr}");
+}
+
+#[test]
+fn rejects_stdin_mixed_with_other_paths() {
+    let dir = TempDir::new().unwrap();
+    let output = run_with_stdin(dir.path(), &["-", "other.rs", "--stdin-filepath", "a.rs"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+}
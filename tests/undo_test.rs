@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_uncomment"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+const ORIGINAL: &str = "// strip me\nfn main() {}\n";
+
+#[test]
+fn undo_restores_the_last_run() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), ORIGINAL).unwrap();
+
+    let run = run_in(dir.path(), &["."]);
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+        "fn main() {}\n"
+    );
+    assert!(String::from_utf8_lossy(&run.stderr).contains("uncomment undo"));
+
+    // The journal itself must never be picked up by a later run.
+    let second = run_in(dir.path(), &[".", "--no-gitignore", "--dry-run"]);
+    assert!(String::from_utf8_lossy(&second.stdout).contains("1 files processed"));
+
+    let undo = run_in(dir.path(), &["undo"]);
+    assert!(undo.status.success(), "{}", String::from_utf8_lossy(&undo.stderr));
+    assert_eq!(fs::read_to_string(dir.path().join("main.rs")).unwrap(), ORIGINAL);
+
+    let list = run_in(dir.path(), &["undo", "--list"]);
+    assert!(String::from_utf8_lossy(&list.stdout).contains("No journaled runs"));
+}
+
+#[test]
+fn undo_refuses_files_edited_after_the_run() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), ORIGINAL).unwrap();
+    assert!(run_in(dir.path(), &["main.rs"]).status.success());
+    fs::write(dir.path().join("main.rs"), "fn main() { edited(); }\n").unwrap();
+
+    let undo = run_in(dir.path(), &["undo"]);
+
+    assert!(!undo.status.success());
+    assert!(String::from_utf8_lossy(&undo.stderr).contains("changed since the run"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+        "fn main() { edited(); }\n"
+    );
+}
+
+#[test]
+fn dry_runs_and_no_journal_leave_no_history() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), ORIGINAL).unwrap();
+
+    assert!(run_in(dir.path(), &["main.rs", "--dry-run"]).status.success());
+    assert!(run_in(dir.path(), &["main.rs", "--no-journal"]).status.success());
+
+    assert!(!dir.path().join(".uncomment").exists());
+}